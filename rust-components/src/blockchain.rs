@@ -1,11 +1,54 @@
-use serde::{Serialize, Deserialize};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::error::Error;
+use std::fmt;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::crypto;
+use crate::crypto::{self, HashAlgorithm};
+
+/// 默认的基础手续费
+pub const DEFAULT_BASE_FEE: u64 = 21000;
+
+/// 默认的每字节数据手续费
+pub const DEFAULT_FEE_PER_BYTE: u64 = 16;
+
+/// 数据访问交易 `data` 字段的固定前缀，后接数据ID或CID
+pub const DATA_ACCESS_PREFIX: &str = "Access granted to data: ";
+
+/// 检查幂等键重复时回溯的最近区块数量
+pub const IDEMPOTENCY_WINDOW_BLOCKS: usize = 100;
+
+/// 测试模式下使用的挖矿难度
+pub const TEST_DIFFICULTY: u8 = 1;
+
+/// 演示链创世区块的时间戳
+const DEMO_START_TIMESTAMP: u64 = 1_700_000_000;
+
+/// 演示链中相邻区块的时间间隔（秒）
+const DEMO_BLOCK_INTERVAL_SECS: u64 = 600;
+
+/// 默认的待处理交易池容量
+pub const DEFAULT_MAX_MEMPOOL_SIZE: usize = 10_000;
+
+fn default_max_mempool_size() -> usize {
+    DEFAULT_MAX_MEMPOOL_SIZE
+}
+
+/// 默认允许交易时间戳超前本地时钟的秒数
+pub const DEFAULT_MAX_FUTURE_SKEW_SECS: u64 = 120;
+
+fn default_max_future_skew_secs() -> u64 {
+    DEFAULT_MAX_FUTURE_SKEW_SECS
+}
 
 /// 区块链中的交易类型
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// 序列化为 `{"type": "DataAccess"}` 形式，`Custom` 的内容放在 `value` 字段中，
+/// 例如 `{"type": "Custom", "value": "foo"}`。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
 pub enum TransactionType {
     DataSubmission,
     DataAccess,
@@ -15,10 +58,53 @@ pub enum TransactionType {
     Custom(String),
 }
 
+/// 旧版本使用的外部标签格式，例如 `"DataAccess"` 或 `{"Custom": "foo"}`
+#[derive(Deserialize)]
+enum LegacyTransactionType {
+    DataSubmission,
+    DataAccess,
+    CredentialVerification,
+    TokenTransfer,
+    SmartContractInteraction,
+    Custom(String),
+}
+
+impl From<LegacyTransactionType> for TransactionType {
+    fn from(legacy: LegacyTransactionType) -> Self {
+        match legacy {
+            LegacyTransactionType::DataSubmission => TransactionType::DataSubmission,
+            LegacyTransactionType::DataAccess => TransactionType::DataAccess,
+            LegacyTransactionType::CredentialVerification => TransactionType::CredentialVerification,
+            LegacyTransactionType::TokenTransfer => TransactionType::TokenTransfer,
+            LegacyTransactionType::SmartContractInteraction => TransactionType::SmartContractInteraction,
+            LegacyTransactionType::Custom(name) => TransactionType::Custom(name),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TransactionTypeRepr {
+    Current(TransactionType),
+    Legacy(LegacyTransactionType),
+}
+
+/// 反序列化交易类型，同时兼容旧版本生成的JSON
+fn deserialize_transaction_type<'de, D>(deserializer: D) -> Result<TransactionType, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match TransactionTypeRepr::deserialize(deserializer)? {
+        TransactionTypeRepr::Current(tx_type) => tx_type,
+        TransactionTypeRepr::Legacy(legacy) => legacy.into(),
+    })
+}
+
 /// 区块链交易
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transaction {
     pub id: String,
+    #[serde(deserialize_with = "deserialize_transaction_type")]
     pub transaction_type: TransactionType,
     pub sender: String,
     pub recipient: Option<String>,
@@ -27,6 +113,15 @@ pub struct Transaction {
     pub signature: Option<String>,
     pub gas_fee: Option<u64>,
     pub status: TransactionStatus,
+    /// 附加的键值元数据，参与签名
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// 客户端提供的幂等键，重试提交时用于识别重复交易
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    /// 多重签名：(公钥, 签名) 列表，签名对象与 `signature` 相同
+    #[serde(default)]
+    pub signatures: Vec<(String, String)>,
 }
 
 /// 交易状态
@@ -62,21 +157,57 @@ impl Transaction {
             signature: None,
             gas_fee: None,
             status: TransactionStatus::Pending,
+            metadata: HashMap::new(),
+            idempotency_key: None,
+            signatures: Vec::new(),
         }
     }
     
+    /// 创建一笔数据访问交易，`data` 字段采用统一格式以便按数据ID检索
+    pub fn data_access(sender: &str, data_id: &str) -> Self {
+        Transaction::new(
+            TransactionType::DataAccess,
+            sender,
+            &format!("{}{}", DATA_ACCESS_PREFIX, data_id),
+        )
+    }
+    
     /// 设置交易接收方
     pub fn with_recipient(mut self, recipient: &str) -> Self {
         self.recipient = Some(recipient.to_string());
         self
     }
     
+    /// 设置交易时间戳，并据此重新计算交易ID
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = timestamp;
+        self.id = crypto::hash_sha256(&format!("{}{}{}", self.sender, timestamp, self.data));
+        self
+    }
+    
     /// 设置交易手续费
     pub fn with_gas_fee(mut self, gas_fee: u64) -> Self {
         self.gas_fee = Some(gas_fee);
         self
     }
     
+    /// 按数据大小估算手续费：`base_fee + per_byte * data.len()`
+    pub fn estimate_fee(&self, base_fee: u64, per_byte: u64) -> u64 {
+        base_fee.saturating_add(per_byte.saturating_mul(self.data.len() as u64))
+    }
+    
+    /// 设置幂等键
+    pub fn with_idempotency_key(mut self, key: &str) -> Self {
+        self.idempotency_key = Some(key.to_string());
+        self
+    }
+    
+    /// 添加一条元数据
+    pub fn with_metadata(mut self, key: &str, value: &str) -> Self {
+        self.metadata.insert(key.to_string(), value.to_string());
+        self
+    }
+    
     /// 对交易进行签名
     pub fn sign(&mut self, private_key: &str) -> Result<(), Box<dyn Error>> {
         let message = self.to_signing_string();
@@ -85,6 +216,18 @@ impl Transaction {
         Ok(())
     }
     
+    /// 待签名内容的UTF-8字节，供硬件钱包等外部签名方使用
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        self.to_signing_string().into_bytes()
+    }
+    
+    /// 写入由外部签名方对 `signing_bytes` 生成的签名
+    ///
+    /// 不会校验签名，提交到链上时由 `add_transaction` 校验。
+    pub fn apply_signature(&mut self, signature: String) {
+        self.signature = Some(signature);
+    }
+    
     /// 验证交易签名
     pub fn verify_signature(&self, public_key: &str) -> bool {
         if let Some(ref signature) = self.signature {
@@ -95,16 +238,44 @@ impl Transaction {
         }
     }
     
-    /// 生成待签名的字符串
-    fn to_signing_string(&self) -> String {
-        format!(
-            "{}:{}:{}:{}:{}",
-            self.id,
-            self.sender,
-            self.recipient.clone().unwrap_or_default(),
-            self.timestamp,
-            self.data
-        )
+    /// 追加一个多重签名，签名方公钥由私钥推导
+    pub fn add_signature(&mut self, private_key: &str) -> Result<(), Box<dyn Error>> {
+        let public_key = crypto::derive_public_key(private_key);
+        let signature = crypto::sign_data(&self.to_signing_string(), private_key)?;
+        
+        self.signatures.retain(|(key, _)| *key != public_key);
+        self.signatures.push((public_key, signature));
+        Ok(())
+    }
+    
+    /// 验证 `required_keys` 中至少有 `threshold` 个公钥提供了有效签名
+    ///
+    /// 每个公钥最多计数一次，不在 `required_keys` 中的签名会被忽略。
+    pub fn verify_multisig(&self, required_keys: &[String], threshold: usize) -> bool {
+        let message = self.to_signing_string();
+        let signed_keys: HashSet<&String> = self
+            .signatures
+            .iter()
+            .filter(|(key, signature)| {
+                required_keys.contains(key) && crypto::verify_signature(&message, signature, key)
+            })
+            .map(|(key, _)| key)
+            .collect();
+        
+        signed_keys.len() >= threshold
+    }
+    
+    /// 生成待签名的字符串（规范化JSON，键名有序且无空白）
+    pub fn to_signing_string(&self) -> String {
+        crypto::canonical_json_value(&serde_json::json!({
+            "id": self.id,
+            "sender": self.sender,
+            "recipient": self.recipient,
+            "timestamp": self.timestamp,
+            "data": self.data,
+            "metadata": self.metadata,
+            "idempotency_key": self.idempotency_key,
+        }))
     }
     
     /// 序列化为JSON
@@ -130,6 +301,21 @@ pub struct Block {
     pub hash: String,
     pub nonce: u64,
     pub difficulty: u8,
+    /// 以前导零比特数表示的挖矿目标，仅在 `use_target_bits` 为真时生效
+    #[serde(default)]
+    pub target_bits: u32,
+    /// 为真时按前导零比特数校验，否则沿用 `difficulty` 的十六进制前缀
+    #[serde(default)]
+    pub use_target_bits: bool,
+    /// 计算区块哈希所用的算法
+    #[serde(default)]
+    pub hash_algorithm: HashAlgorithm,
+    /// 便于运维人员识别的区块标签
+    #[serde(default)]
+    pub label: Option<String>,
+    /// 为真时标签参与区块哈希计算，默认不参与以免影响已有区块的校验
+    #[serde(default)]
+    pub hash_includes_label: bool,
 }
 
 impl Block {
@@ -148,6 +334,11 @@ impl Block {
             hash: String::new(),
             nonce: 0,
             difficulty,
+            target_bits: 0,
+            use_target_bits: false,
+            hash_algorithm: HashAlgorithm::Sha256,
+            label: None,
+            hash_includes_label: false,
         };
         
         block.hash = block.calculate_hash();
@@ -161,32 +352,98 @@ impl Block {
             tx_data.push_str(&tx.id);
         }
         
-        crypto::hash_sha256(&format!(
+        let mut content = format!(
             "{}{}{}{}{}",
             self.index,
             self.previous_hash,
             self.timestamp,
             tx_data,
             self.nonce
-        ))
+        );
+        if self.hash_includes_label {
+            if let Some(ref label) = self.label {
+                content.push_str(label);
+            }
+        }
+        
+        self.hash_algorithm.hash(&content)
+    }
+    
+    /// 使用指定的哈希算法计算区块哈希
+    pub fn with_hash_algorithm(mut self, hash_algorithm: HashAlgorithm) -> Self {
+        self.hash_algorithm = hash_algorithm;
+        self.hash = self.calculate_hash();
+        self
+    }
+    
+    /// 让标签参与区块哈希计算
+    pub fn with_label_in_hash(mut self) -> Self {
+        self.hash_includes_label = true;
+        self.hash = self.calculate_hash();
+        self
+    }
+    
+    /// 设置区块标签
+    ///
+    /// 若标签参与哈希计算，会重新计算哈希，因此应在挖矿之前设置。
+    pub fn set_label(&mut self, label: &str) {
+        self.label = Some(label.to_string());
+        if self.hash_includes_label {
+            self.hash = self.calculate_hash();
+        }
+    }
+    
+    /// 改用前导零比特数作为挖矿目标
+    pub fn with_target_bits(mut self, target_bits: u32) -> Self {
+        self.target_bits = target_bits;
+        self.use_target_bits = true;
+        self
+    }
+    
+    /// 判断哈希是否满足当前的挖矿目标
+    fn meets_target(&self, hash: &str) -> bool {
+        if self.use_target_bits {
+            leading_zero_bits(hash) >= self.target_bits
+        } else {
+            let target_prefix = "0".repeat(self.difficulty as usize);
+            hash.starts_with(&target_prefix)
+        }
     }
     
     /// 挖掘区块以满足难度要求
     pub fn mine(&mut self) {
-        let target_prefix = "0".repeat(self.difficulty as usize);
-        
-        while !self.hash.starts_with(&target_prefix) {
+        while !self.meets_target(&self.hash) {
             self.nonce += 1;
             self.hash = self.calculate_hash();
         }
     }
     
+    /// 计算区块内所有交易的手续费总和
+    pub fn total_gas_fees(&self) -> u64 {
+        self.transactions
+            .iter()
+            .filter_map(|tx| tx.gas_fee)
+            .sum()
+    }
+    
+    /// 挖出该区块所需的期望哈希次数（工作量）
+    ///
+    /// 比特目标模式下为 `2^target_bits`；十六进制前缀模式下每个十六进制零
+    /// 相当于4个零比特，即 `2^(4 * difficulty)`。
+    pub fn work(&self) -> u128 {
+        let bits = if self.use_target_bits {
+            self.target_bits
+        } else {
+            self.difficulty as u32 * 4
+        };
+        work_for_zero_bits(bits)
+    }
+    
     /// 验证区块是否有效
     pub fn is_valid(&self) -> bool {
-        let target_prefix = "0".repeat(self.difficulty as usize);
         let calculated_hash = self.calculate_hash();
         
-        calculated_hash == self.hash && self.hash.starts_with(&target_prefix)
+        calculated_hash == self.hash && self.meets_target(&self.hash)
     }
     
     /// 序列化为JSON
@@ -202,23 +459,304 @@ impl Block {
     }
 }
 
+/// 计算十六进制哈希按字节解释后的前导零比特数
+pub fn leading_zero_bits(hash: &str) -> u32 {
+    let bytes = match hex::decode(hash) {
+        Ok(bytes) => bytes,
+        Err(_) => return 0,
+    };
+    
+    let mut bits = 0;
+    for byte in bytes {
+        if byte == 0 {
+            bits += 8;
+        } else {
+            bits += byte.leading_zeros();
+            break;
+        }
+    }
+    bits
+}
+
+/// 分叉处理结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForkOutcome {
+    /// 是否切换到了新分支
+    pub reorganized: bool,
+    /// 被回滚的本地区块数量
+    pub rolled_back: usize,
+}
+
+/// 管理操作审计日志中的一条记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub index: u64,
+    pub timestamp: u64,
+    pub action: String,
+    /// 前一条记录的哈希，第一条记录为 "0"
+    pub previous_hash: String,
+    pub hash: String,
+}
+
+impl AuditEntry {
+    /// 计算记录的哈希（SHA-256，覆盖除哈希本身以外的全部字段）
+    pub fn calculate_hash(&self) -> String {
+        crypto::hash_sha256(&crypto::canonical_json_value(&serde_json::json!({
+            "index": self.index,
+            "timestamp": self.timestamp,
+            "action": self.action,
+            "previous_hash": self.previous_hash,
+        })))
+    }
+}
+
+/// 按确认数判断的交易最终性状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status")]
+pub enum FinalityStatus {
+    /// 链上和待处理队列中都找不到该交易
+    Unknown,
+    /// 交易仍在待处理队列中
+    Pending,
+    /// 交易已上链，但确认数尚未达到要求
+    Confirming { confirmations: u64 },
+    /// 确认数已达到要求
+    Final,
+}
+
+/// 区块哈希在 Merkle Mountain Range 中的包含证明
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MmrProof {
+    /// 区块在链中的位置（叶子序号）
+    pub leaf_index: u64,
+    /// 生成证明时的叶子总数，即链长度
+    pub leaf_count: u64,
+    /// 从叶子到所在山峰的兄弟节点哈希，自下而上
+    pub siblings: Vec<String>,
+    /// 所有山峰的根哈希，从左到右
+    pub peaks: Vec<String>,
+}
+
+impl MmrProof {
+    /// 验证 `block_hash` 位于以 `root` 为根的 MMR 中本证明所指的位置
+    pub fn verify(&self, block_hash: &str, root: &str) -> bool {
+        let sizes = mmr_peak_sizes(self.leaf_count);
+        if self.leaf_index >= self.leaf_count || sizes.len() != self.peaks.len() {
+            return false;
+        }
+        
+        // 找到叶子所在的山峰及其在山峰内的序号
+        let mut start = 0;
+        let mut peak = 0;
+        while self.leaf_index >= start + sizes[peak] {
+            start += sizes[peak];
+            peak += 1;
+        }
+        if 1u64 << self.siblings.len() != sizes[peak] {
+            return false;
+        }
+        
+        let mut local_index = self.leaf_index - start;
+        let mut hash = block_hash.to_string();
+        for sibling in &self.siblings {
+            hash = if local_index & 1 == 0 {
+                mmr_hash(&hash, sibling)
+            } else {
+                mmr_hash(sibling, &hash)
+            };
+            local_index /= 2;
+        }
+        
+        hash == self.peaks[peak] && bag_peaks(&self.peaks) == root
+    }
+}
+
+/// 新区块挖出后调用的回调
+pub type BlockCallback = Box<dyn Fn(&Block)>;
+
+/// 新区块挖出后依次调用的回调列表
+#[derive(Default)]
+struct BlockObservers(Vec<BlockCallback>);
+
+impl fmt::Debug for BlockObservers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BlockObservers({})", self.0.len())
+    }
+}
+
+/// 处理某一标签自定义交易的回调
+pub type CustomHandler = Box<dyn Fn(&Transaction) -> Result<(), Box<dyn Error>>>;
+
+/// 按 `TransactionType::Custom` 标签分发交易的处理函数表
+#[derive(Default)]
+pub struct HandlerRegistry {
+    handlers: HashMap<String, CustomHandler>,
+}
+
+impl HandlerRegistry {
+    pub fn new() -> Self {
+        HandlerRegistry::default()
+    }
+    
+    /// 为标签注册处理函数，替换已有的处理函数
+    pub fn register(&mut self, tag: &str, handler: CustomHandler) {
+        self.handlers.insert(tag.to_string(), handler);
+    }
+    
+    /// 标签是否已注册处理函数
+    pub fn contains(&self, tag: &str) -> bool {
+        self.handlers.contains_key(tag)
+    }
+    
+    /// 将自定义交易交给对应标签的处理函数
+    pub fn dispatch(&self, transaction: &Transaction) -> Result<(), Box<dyn Error>> {
+        let tag = match transaction.transaction_type {
+            TransactionType::Custom(ref tag) => tag,
+            ref other => return Err(format!("不是自定义交易: {:?}", other).into()),
+        };
+        let handler = self
+            .handlers
+            .get(tag)
+            .ok_or_else(|| format!("未注册的自定义交易标签: {}", tag))?;
+        handler(transaction)
+    }
+}
+
+impl fmt::Debug for HandlerRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tags: Vec<&String> = self.handlers.keys().collect();
+        tags.sort();
+        write!(f, "HandlerRegistry({:?})", tags)
+    }
+}
+
+/// 按键排序序列化映射，使相同内容的JSON输出一致
+fn serialize_sorted<S: Serializer>(map: &HashMap<String, String>, serializer: S) -> Result<S::Ok, S::Error> {
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+/// 转义 DOT 字符串中的反斜杠和引号
+fn dot_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// MMR 内部节点的哈希
+fn mmr_hash(left: &str, right: &str) -> String {
+    crypto::hash_sha256(&format!("mmr:{}:{}", left, right))
+}
+
+/// `leaf_count` 个叶子组成的各山峰大小（2的幂），从左到右递减
+fn mmr_peak_sizes(leaf_count: u64) -> Vec<u64> {
+    (0..64)
+        .rev()
+        .map(|bit| 1u64 << bit)
+        .filter(|size| leaf_count & size != 0)
+        .collect()
+}
+
+/// 由右向左合并各山峰得到 MMR 根
+fn bag_peaks(peaks: &[String]) -> String {
+    let mut iter = peaks.iter().rev();
+    let last = match iter.next() {
+        Some(last) => last.clone(),
+        None => return String::new(),
+    };
+    iter.fold(last, |root, peak| mmr_hash(peak, &root))
+}
+
+/// 完全二叉 Merkle 树的根，以及（可选）指定叶子的兄弟节点路径
+fn merkle_root_and_path(leaves: &[String], index: Option<usize>) -> (String, Vec<String>) {
+    let mut level = leaves.to_vec();
+    let mut index = index;
+    let mut path = Vec::new();
+    
+    while level.len() > 1 {
+        if let Some(i) = index {
+            path.push(level[i ^ 1].clone());
+            index = Some(i / 2);
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| mmr_hash(&pair[0], &pair[1]))
+            .collect();
+    }
+    
+    (level.pop().unwrap_or_default(), path)
+}
+
+/// 当前的Unix时间戳（秒）
+fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// 要求 `bits` 个前导零比特的目标对应的工作量
+fn work_for_zero_bits(bits: u32) -> u128 {
+    1u128.checked_shl(bits).unwrap_or(u128::MAX)
+}
+
+/// 一组区块的累计工作量
+fn chain_work(blocks: &[Block]) -> u128 {
+    blocks.iter().fold(0u128, |total, block| total.saturating_add(block.work()))
+}
+
 /// 简单的区块链实现
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Blockchain {
     pub chain: Vec<Block>,
     pub pending_transactions: Vec<Transaction>,
+    /// 待处理交易池容量，达到上限后按手续费淘汰
+    #[serde(default = "default_max_mempool_size")]
+    pub max_mempool_size: usize,
+    /// 交易时间戳最多可超前本地时钟的秒数
+    #[serde(default = "default_max_future_skew_secs")]
+    pub max_future_skew_secs: u64,
     pub difficulty: u8,
     pub mining_reward: u64,
+    /// 发送方地址到公钥的映射，用于验证交易签名
+    #[serde(default, serialize_with = "serialize_sorted")]
+    pub public_keys: HashMap<String, String>,
+    /// 整条链统一使用的区块哈希算法
+    #[serde(default)]
+    pub hash_algorithm: HashAlgorithm,
+    /// 受信任的检查点：区块高度到区块哈希的映射
+    ///
+    /// 检查点是本地配置，不随链序列化，以免对端在JSON中注入检查点跳过验证；
+    /// 从JSON加载的链需要重新添加检查点。
+    #[serde(skip)]
+    pub checkpoints: BTreeMap<u64, String>,
+    /// 只追加的管理操作审计日志，独立于交易链
+    #[serde(default)]
+    pub audit_log: Vec<AuditEntry>,
+    #[serde(skip)]
+    block_observers: BlockObservers,
+    #[serde(skip)]
+    custom_handlers: HandlerRegistry,
 }
 
 impl Blockchain {
     /// 创建一个新的区块链，并初始化创世区块
     pub fn new(difficulty: u8, mining_reward: u64) -> Self {
+        Self::with_hash_algorithm(difficulty, mining_reward, HashAlgorithm::Sha256)
+    }
+    
+    /// 创建一个使用指定哈希算法的区块链，并初始化创世区块
+    pub fn with_hash_algorithm(difficulty: u8, mining_reward: u64, hash_algorithm: HashAlgorithm) -> Self {
         let mut blockchain = Blockchain {
             chain: Vec::new(),
             pending_transactions: Vec::new(),
+            max_mempool_size: DEFAULT_MAX_MEMPOOL_SIZE,
+            max_future_skew_secs: DEFAULT_MAX_FUTURE_SKEW_SECS,
             difficulty,
             mining_reward,
+            public_keys: HashMap::new(),
+            hash_algorithm,
+            checkpoints: BTreeMap::new(),
+            audit_log: Vec::new(),
+            block_observers: BlockObservers::default(),
+            custom_handlers: HandlerRegistry::default(),
         };
         
         // 创建创世区块
@@ -226,9 +764,84 @@ impl Blockchain {
         blockchain
     }
     
+    /// 将挖矿难度固定为 `TEST_DIFFICULTY`，让测试中的挖矿快速且稳定
+    ///
+    /// 已有区块的难度不低于该值，因此整条链仍能通过验证。
+    pub fn with_test_difficulty(mut self) -> Self {
+        self.difficulty = TEST_DIFFICULTY;
+        self
+    }
+    
+    /// 设置待处理交易池容量
+    pub fn with_max_mempool_size(mut self, max_mempool_size: usize) -> Self {
+        self.max_mempool_size = max_mempool_size;
+        self
+    }
+    
+    /// 设置交易时间戳允许超前本地时钟的秒数
+    pub fn with_max_future_skew_secs(mut self, max_future_skew_secs: u64) -> Self {
+        self.max_future_skew_secs = max_future_skew_secs;
+        self
+    }
+    
+    /// 生成可复现的演示链，相同的种子得到完全相同的链
+    ///
+    /// 由种子派生几位研究人员的密钥，提交并挖出若干数据提交和数据访问交易。
+    /// 所有时间戳都从固定的起点递增，不依赖本地时钟。
+    pub fn demo_chain(seed: u64) -> Blockchain {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut blockchain = Blockchain::new(TEST_DIFFICULTY, 50);
+        let genesis = &mut blockchain.chain[0];
+        genesis.timestamp = DEMO_START_TIMESTAMP;
+        genesis.hash = genesis.calculate_hash();
+        
+        let researchers: Vec<(String, String)> = (1..=3)
+            .map(|i| {
+                let address = format!("researcher_{:03}", i);
+                let (private_key, public_key) = crypto::keypair_from_seed(&rng.gen::<[u8; 32]>());
+                blockchain.register_public_key_at(&address, &public_key, DEMO_START_TIMESTAMP);
+                (address, private_key)
+            })
+            .collect();
+        
+        let mut datasets: Vec<String> = Vec::new();
+        for round in 1..=4u64 {
+            let block_timestamp = DEMO_START_TIMESTAMP + round * DEMO_BLOCK_INTERVAL_SECS;
+            let mut transactions = Vec::new();
+            
+            for offset in 0..3u64 {
+                let (address, private_key) = &researchers[rng.gen_range(0..researchers.len())];
+                let timestamp = block_timestamp - DEMO_BLOCK_INTERVAL_SECS + 1 + offset;
+                
+                // 先提交数据集，之后的交易随机访问已有数据集
+                let transaction = if datasets.is_empty() || rng.gen_bool(0.5) {
+                    let dataset = format!("ds-{:08x}", rng.gen::<u32>());
+                    datasets.push(dataset.clone());
+                    Transaction::new(TransactionType::DataSubmission, address, &dataset)
+                } else {
+                    let dataset = &datasets[rng.gen_range(0..datasets.len())];
+                    Transaction::data_access(address, dataset)
+                };
+                let transaction = transaction.with_timestamp(timestamp);
+                let fee = transaction.estimate_fee(DEFAULT_BASE_FEE, DEFAULT_FEE_PER_BYTE);
+                let mut transaction = transaction.with_gas_fee(fee);
+                transaction.sign(private_key).expect("演示交易签名失败");
+                transactions.push(transaction);
+            }
+            
+            let miner = &researchers[rng.gen_range(0..researchers.len())].0;
+            blockchain
+                .mine_block_at(transactions, miner, block_timestamp)
+                .expect("演示区块无效");
+        }
+        
+        blockchain
+    }
+    
     /// 创建创世区块
     fn create_genesis_block(&mut self) {
-        let genesis_block = Block::new(0, "0", Vec::new(), self.difficulty);
+        let genesis_block = Block::new(0, "0", Vec::new(), self.difficulty)
+            .with_hash_algorithm(self.hash_algorithm);
         self.chain.push(genesis_block);
     }
     
@@ -237,31 +850,207 @@ impl Blockchain {
         self.chain.last()
     }
     
+    /// 登记发送方地址对应的公钥
+    pub fn register_public_key(&mut self, address: &str, public_key: &str) {
+        self.register_public_key_at(address, public_key, current_timestamp());
+    }
+    
+    fn register_public_key_at(&mut self, address: &str, public_key: &str, timestamp: u64) {
+        self.public_keys.insert(address.to_string(), public_key.to_string());
+        self.append_audit_at(&format!("register_public_key {} {}", address, public_key), timestamp);
+    }
+    
+    /// 向审计日志追加一条管理操作记录
+    pub fn append_audit(&mut self, action: &str) -> &AuditEntry {
+        self.append_audit_at(action, current_timestamp())
+    }
+    
+    fn append_audit_at(&mut self, action: &str, timestamp: u64) -> &AuditEntry {
+        let (index, previous_hash) = match self.audit_log.last() {
+            Some(last) => (last.index + 1, last.hash.clone()),
+            None => (0, "0".to_string()),
+        };
+        
+        let mut entry = AuditEntry {
+            index,
+            timestamp,
+            action: action.to_string(),
+            previous_hash,
+            hash: String::new(),
+        };
+        entry.hash = entry.calculate_hash();
+        self.audit_log.push(entry);
+        &self.audit_log[self.audit_log.len() - 1]
+    }
+    
+    /// 验证审计日志的哈希链是否完整
+    pub fn verify_audit_log(&self) -> bool {
+        let mut previous_hash = "0";
+        for (i, entry) in self.audit_log.iter().enumerate() {
+            if entry.index != i as u64
+                || entry.previous_hash != previous_hash
+                || entry.calculate_hash() != entry.hash
+            {
+                return false;
+            }
+            previous_hash = &entry.hash;
+        }
+        true
+    }
+    
     /// 添加一个待处理交易
     pub fn add_transaction(&mut self, transaction: Transaction) -> Result<(), Box<dyn Error>> {
-        // 此处可以添加更多验证逻辑
+        // 奖励交易只在挖矿时由链自身生成，不接受外部提交
+        if transaction.sender == "System" {
+            return Err("不接受外部提交的系统交易".into());
+        }
+        
         if transaction.signature.is_none() {
             return Err("交易缺少签名".into());
         }
         
+        let public_key = self
+            .public_keys
+            .get(&transaction.sender)
+            .ok_or_else(|| format!("未登记公钥的发送方: {}", transaction.sender))?;
+        
+        if !transaction.verify_signature(public_key) {
+            return Err("交易签名无效".into());
+        }
+        
+        let now = current_timestamp();
+        if transaction.timestamp > now.saturating_add(self.max_future_skew_secs) {
+            return Err(format!(
+                "交易时间戳超前本地时钟 {} 秒，超过允许的 {} 秒",
+                transaction.timestamp - now,
+                self.max_future_skew_secs
+            ).into());
+        }
+        
+        if let Some(ref key) = transaction.idempotency_key {
+            if self.has_idempotency_key(&transaction.sender, key) {
+                return Err(format!("重复的幂等键: {}", key).into());
+            }
+        }
+        
+        // 交易池已满时淘汰手续费最低的交易，新交易手续费不高于它时直接拒绝
+        if self.pending_transactions.len() >= self.max_mempool_size {
+            let fee = transaction.gas_fee.unwrap_or(0);
+            let lowest = self
+                .pending_transactions
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, tx)| tx.gas_fee.unwrap_or(0))
+                .map(|(i, tx)| (i, tx.gas_fee.unwrap_or(0)));
+            
+            match lowest {
+                Some((i, lowest_fee)) if fee > lowest_fee => {
+                    self.pending_transactions.remove(i);
+                }
+                _ => return Err("交易池已满且手续费过低".into()),
+            }
+        }
+        
         self.pending_transactions.push(transaction);
         Ok(())
     }
     
+    /// 合并对等节点的待处理交易，返回实际加入的交易数
+    ///
+    /// 已在待处理队列或链上的交易（按ID判断）以及未通过 `add_transaction` 校验的交易会被跳过。
+    pub fn merge_mempool(&mut self, incoming: &[Transaction]) -> usize {
+        let mut added = 0;
+        for transaction in incoming {
+            if self.find_transaction(&transaction.id).is_some() {
+                continue;
+            }
+            if self.add_transaction(transaction.clone()).is_ok() {
+                added += 1;
+            }
+        }
+        added
+    }
+    
+    /// 构建、签名并提交一笔交易到待处理队列，返回交易ID
+    ///
+    /// 手续费按数据大小以默认费率估算。
+    pub fn submit(
+        &mut self,
+        tx_type: TransactionType,
+        sender: &str,
+        data: &str,
+        private_key: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let transaction = Transaction::new(tx_type, sender, data);
+        let fee = transaction.estimate_fee(DEFAULT_BASE_FEE, DEFAULT_FEE_PER_BYTE);
+        let mut transaction = transaction.with_gas_fee(fee);
+        transaction.sign(private_key)?;
+        
+        let id = transaction.id.clone();
+        self.add_transaction(transaction)?;
+        Ok(id)
+    }
+    
+    /// 检查待处理队列或最近区块中是否已有该发送方使用此幂等键的交易
+    ///
+    /// 幂等键按发送方隔离，其他发送方无法抢先占用某个发送方的键。
+    fn has_idempotency_key(&self, sender: &str, key: &str) -> bool {
+        let matches = |tx: &Transaction| tx.sender == sender && tx.idempotency_key.as_deref() == Some(key);
+        
+        self.pending_transactions.iter().any(matches)
+            || self
+                .chain
+                .iter()
+                .rev()
+                .take(IDEMPOTENCY_WINDOW_BLOCKS)
+                .any(|block| block.transactions.iter().any(matches))
+    }
+    
+    /// 注册新区块挖出后的回调
+    pub fn on_block_mined(&mut self, callback: BlockCallback) {
+        self.block_observers.0.push(callback);
+    }
+    
+    /// 为 `TransactionType::Custom` 标签注册处理函数
+    pub fn register_custom_handler(&mut self, tag: &str, handler: CustomHandler) {
+        self.custom_handlers.register(tag, handler);
+    }
+    
+    /// 用已注册的处理函数处理自定义交易，标签未注册或不是自定义交易时返回错误
+    pub fn process_custom(&self, transaction: &Transaction) -> Result<(), Box<dyn Error>> {
+        self.custom_handlers.dispatch(transaction)
+    }
+    
     /// 挖掘待处理交易并创建新区块
     pub fn mine_pending_transactions(&mut self, miner_address: &str) -> Result<Block, Box<dyn Error>> {
         if self.pending_transactions.is_empty() {
             return Err("没有待处理的交易可挖掘".into());
         }
         
+        let transactions = self.pending_transactions.clone();
+        let block = self.mine_block(transactions, miner_address)?;
+        self.pending_transactions = Vec::new(); // 清空待处理交易
+        Ok(block)
+    }
+    
+    /// 挖掘只包含奖励交易的空区块（心跳区块），不影响待处理队列
+    pub fn mine_empty_block(&mut self, miner_address: &str) -> Result<Block, Box<dyn Error>> {
+        self.mine_block(Vec::new(), miner_address)
+    }
+    
+    /// 挖掘包含给定交易和奖励交易的新区块，并将其加入链中
+    fn mine_block(&mut self, transactions: Vec<Transaction>, miner_address: &str) -> Result<Block, Box<dyn Error>> {
+        self.mine_block_at(transactions, miner_address, current_timestamp())
+    }
+    
+    /// 以指定时间戳挖出区块，奖励交易使用同一时间戳
+    fn mine_block_at(&mut self, transactions: Vec<Transaction>, miner_address: &str, timestamp: u64) -> Result<Block, Box<dyn Error>> {
         // 添加奖励交易
-        let reward_tx = Transaction::new(
-            TransactionType::TokenTransfer,
-            "System",
-            &format!("Reward: {}", self.mining_reward),
-        ).with_recipient(miner_address);
+        let reward_tx = Transaction::new(TransactionType::TokenTransfer, "System", &self.reward_data())
+        .with_recipient(miner_address)
+        .with_timestamp(timestamp);
         
-        let mut transactions_to_mine = self.pending_transactions.clone();
+        let mut transactions_to_mine = transactions;
         transactions_to_mine.push(reward_tx);
         
         // 获取最新区块的索引和哈希
@@ -270,13 +1059,19 @@ impl Blockchain {
         let previous_hash = latest_block.hash.clone();
         
         // 创建新区块并挖掘
-        let mut new_block = Block::new(new_index, &previous_hash, transactions_to_mine, self.difficulty);
+        let mut new_block = Block::new(new_index, &previous_hash, transactions_to_mine, self.difficulty)
+            .with_hash_algorithm(self.hash_algorithm);
+        new_block.timestamp = timestamp;
+        new_block.hash = new_block.calculate_hash();
         new_block.mine();
         
         // 验证并添加区块
         if self.is_valid_new_block(&new_block, latest_block) {
             self.chain.push(new_block.clone());
-            self.pending_transactions = Vec::new(); // 清空待处理交易
+            
+            for callback in &self.block_observers.0 {
+                callback(&new_block);
+            }
             Ok(new_block)
         } else {
             Err("无效的区块".into())
@@ -285,37 +1080,302 @@ impl Blockchain {
     
     /// 验证新区块是否有效
     fn is_valid_new_block(&self, new_block: &Block, previous_block: &Block) -> bool {
-        if new_block.index != previous_block.index + 1 {
-            return false;
-        }
+        self.new_block_error(new_block, previous_block).is_none()
+    }
+    
+    /// 新区块无效的原因，有效时返回 `None`
+    fn new_block_error(&self, new_block: &Block, previous_block: &Block) -> Option<&'static str> {
+        if !Self::is_linked(new_block, previous_block) {
+            return Some("broken link");
+        }
         
-        if new_block.previous_hash != previous_block.hash {
-            return false;
+        if new_block.hash_algorithm != self.hash_algorithm {
+            return Some("hash algorithm mismatch");
         }
         
-        if !new_block.is_valid() {
-            return false;
+        if new_block.calculate_hash() != new_block.hash {
+            return Some("hash mismatch");
         }
         
-        true
+        // 区块只按自身声明的目标校验哈希，因此还需确认该目标不低于链要求的难度；
+        // 更高的难度代表更多工作量，是允许的
+        if new_block.work() < work_for_zero_bits(self.difficulty as u32 * 4)
+            || !new_block.meets_target(&new_block.hash)
+        {
+            return Some("difficulty unmet");
+        }
+        
+        // 每个区块至多包含一笔奖励交易，且金额须与链的挖矿奖励一致
+        let mut rewards = new_block.transactions.iter().filter(|tx| tx.sender == "System");
+        let reward_data = self.reward_data();
+        let reward_valid = rewards
+            .next()
+            .is_none_or(|tx| tx.transaction_type == TransactionType::TokenTransfer && tx.data == reward_data);
+        if !reward_valid || rewards.next().is_some() {
+            return Some("invalid reward");
+        }
+        
+        None
+    }
+    
+    /// 奖励交易的数据内容
+    fn reward_data(&self) -> String {
+        format!("Reward: {}", self.mining_reward)
+    }
+    
+    /// 区块的索引和前一区块哈希是否与前一区块衔接
+    fn is_linked(block: &Block, previous_block: &Block) -> bool {
+        block.index == previous_block.index + 1 && block.previous_hash == previous_block.hash
+    }
+    
+    /// 将指定高度的区块哈希记录为受信任的检查点
+    pub fn add_checkpoint(&mut self, height: u64) -> Result<(), Box<dyn Error>> {
+        let block = self
+            .chain
+            .get(height as usize)
+            .ok_or_else(|| format!("高度 {} 超出链长度", height))?;
+        self.checkpoints.insert(height, block.hash.clone());
+        Ok(())
+    }
+    
+    /// 验证创世区块：创世区块不经过挖矿，因此不校验难度，只检查结构和哈希
+    fn is_valid_genesis_block(&self, block: &Block) -> bool {
+        block.index == 0
+            && block.previous_hash == "0"
+            && block.hash_algorithm == self.hash_algorithm
+            && block.calculate_hash() == block.hash
     }
     
     /// 验证整个区块链是否有效
+    ///
+    /// 创世区块不要求满足难度。最近一个检查点及其之前的区块只检查索引和
+    /// 哈希链接，不重新计算哈希；检查点区块的哈希必须与记录的可信哈希一致，
+    /// 之后的区块完整验证。
     pub fn is_chain_valid(&self) -> bool {
-        if self.chain.is_empty() {
-            return false;
+        self.find_first_invalid_block().is_none()
+    }
+    
+    /// 按 `is_chain_valid` 的规则查找第一个无效区块，返回其索引和原因
+    ///
+    /// 原因为 "broken link"、"hash mismatch"、"difficulty unmet"、
+    /// "hash algorithm mismatch"、"invalid genesis" 或 "checkpoint mismatch"。
+    /// 整条链有效时返回 `None`。
+    pub fn find_first_invalid_block(&self) -> Option<(usize, String)> {
+        match self.chain.first() {
+            Some(genesis) if self.is_valid_genesis_block(genesis) => {}
+            _ => return Some((0, "invalid genesis".to_string())),
+        }
+        
+        let checkpoint = self
+            .checkpoints
+            .range(..self.chain.len() as u64)
+            .next_back();
+        let trusted_height = checkpoint.map_or(0, |(&height, _)| height as usize);
+        if let Some((&height, hash)) = checkpoint {
+            if self.chain[height as usize].calculate_hash() != *hash {
+                return Some((height as usize, "checkpoint mismatch".to_string()));
+            }
         }
         
         for i in 1..self.chain.len() {
             let current_block = &self.chain[i];
             let previous_block = &self.chain[i - 1];
             
-            if !self.is_valid_new_block(current_block, previous_block) {
-                return false;
+            // 检查点及之前的区块只跳过工作量校验，仍须衔接且哈希与内容一致
+            let error = if i <= trusted_height {
+                if !Self::is_linked(current_block, previous_block) {
+                    Some("broken link")
+                } else if current_block.calculate_hash() != current_block.hash {
+                    Some("hash mismatch")
+                } else {
+                    None
+                }
+            } else {
+                self.new_block_error(current_block, previous_block)
+            };
+            if let Some(reason) = error {
+                return Some((i, reason.to_string()));
             }
         }
         
-        true
+        None
+    }
+    
+    /// 整条链的累计工作量
+    pub fn total_difficulty(&self) -> u128 {
+        chain_work(&self.chain)
+    }
+    
+    /// 用累计工作量更大的有效链替换本地链
+    ///
+    /// 候选链必须与本地链共享创世区块，且不能改写最新检查点及之前的区块。替换成功返回 `true`，
+    /// 已包含在新链中的交易会从待处理队列中移除。
+    pub fn replace_chain(&mut self, new_chain: Vec<Block>) -> Result<bool, Box<dyn Error>> {
+        let genesis = new_chain.first().ok_or("候选链为空")?;
+        if self.chain.first().map(|block| &block.hash) != Some(&genesis.hash) {
+            return Err("候选链的创世区块不同".into());
+        }
+        
+        for pair in new_chain.windows(2) {
+            if !self.is_valid_new_block(&pair[1], &pair[0]) {
+                return Err(format!("候选链中的区块 {} 无效", pair[1].index).into());
+            }
+        }
+        
+        let diverges_at = (0..self.chain.len())
+            .find(|&i| new_chain.get(i).map(|block| &block.hash) != Some(&self.chain[i].hash))
+            .unwrap_or(self.chain.len());
+        if self.rewrites_checkpoint(diverges_at) {
+            return Err("候选链改写了检查点及之前的区块".into());
+        }
+        
+        if chain_work(&new_chain) <= self.total_difficulty() {
+            return Ok(false);
+        }
+        
+        let included_ids: HashSet<String> = new_chain
+            .iter()
+            .flat_map(|block| block.transactions.iter().map(|tx| tx.id.clone()))
+            .collect();
+        self.pending_transactions.retain(|tx| !included_ids.contains(&tx.id));
+        self.chain = new_chain;
+        Ok(true)
+    }
+    
+    /// 处理来自其他节点的竞争分支
+    ///
+    /// `branch` 的第一个区块必须接在本地链的某个区块之后。分支经过验证后，
+    /// 若其累计工作量大于本地链在共同祖先之后的部分，则切换到该分支，
+    /// 被回滚区块中未包含在新分支里的交易会经 `add_transaction` 重新放回待处理队列，
+    /// 未通过校验的交易被丢弃。改写最新检查点及之前区块的分支会被拒绝。
+    pub fn resolve_fork(&mut self, branch: Vec<Block>) -> Result<ForkOutcome, Box<dyn Error>> {
+        let first = branch.first().ok_or("分支为空")?;
+        let ancestor = self
+            .chain
+            .iter()
+            .position(|block| block.hash == first.previous_hash)
+            .ok_or("找不到共同祖先区块")?;
+        if self.rewrites_checkpoint(ancestor + 1) {
+            return Err("分支改写了检查点及之前的区块".into());
+        }
+        
+        let mut previous = &self.chain[ancestor];
+        for block in &branch {
+            if !self.is_valid_new_block(block, previous) {
+                return Err(format!("分支中的区块 {} 无效", block.index).into());
+            }
+            previous = block;
+        }
+        
+        let current_work = chain_work(&self.chain[ancestor + 1..]);
+        let branch_work = chain_work(&branch);
+        
+        if branch_work <= current_work {
+            return Ok(ForkOutcome { reorganized: false, rolled_back: 0 });
+        }
+        
+        let included_ids: HashSet<String> = branch
+            .iter()
+            .flat_map(|block| block.transactions.iter().map(|tx| tx.id.clone()))
+            .collect();
+        self.pending_transactions.retain(|tx| !included_ids.contains(&tx.id));
+        
+        let rolled_back_blocks = self.chain.split_off(ancestor + 1);
+        let rolled_back = rolled_back_blocks.len();
+        self.chain.extend(branch);
+        
+        // 被回滚的交易按新链重新校验后放回待处理队列，奖励交易和失效的交易被丢弃
+        for block in rolled_back_blocks {
+            for tx in block.transactions {
+                if !included_ids.contains(&tx.id) {
+                    let _ = self.add_transaction(tx);
+                }
+            }
+        }
+        
+        Ok(ForkOutcome { reorganized: true, rolled_back })
+    }
+    
+    /// 从 `height` 起替换区块是否会改写最新检查点及之前的区块
+    fn rewrites_checkpoint(&self, height: usize) -> bool {
+        self.checkpoints
+            .keys()
+            .next_back()
+            .is_some_and(|&checkpoint| height as u64 <= checkpoint)
+    }
+    
+    /// 计算整条链收取的手续费总和
+    pub fn total_fees_collected(&self) -> u64 {
+        self.chain.iter().map(|block| block.total_gas_fees()).sum()
+    }
+    
+    /// 最近 `window_seconds` 秒内（以最新区块时间戳为准）每秒确认的交易数
+    ///
+    /// 链上只有创世区块或窗口为0时返回0。
+    pub fn throughput(&self, window_seconds: u64) -> f64 {
+        let latest = match self.chain.last() {
+            Some(block) if self.chain.len() > 1 && window_seconds > 0 => block.timestamp,
+            _ => return 0.0,
+        };
+        let window_start = latest.saturating_sub(window_seconds);
+        
+        let transactions: usize = self.chain
+            .iter()
+            .skip(1)
+            .filter(|block| block.timestamp > window_start)
+            .map(|block| block.transactions.len())
+            .sum();
+        transactions as f64 / window_seconds as f64
+    }
+    
+    /// 相邻区块之间的平均出块时间（秒），只有一个区块时返回0
+    pub fn average_block_time(&self) -> f64 {
+        match (self.chain.first(), self.chain.last()) {
+            (Some(first), Some(last)) if self.chain.len() > 1 => {
+                last.timestamp.saturating_sub(first.timestamp) as f64 / (self.chain.len() - 1) as f64
+            }
+            _ => 0.0,
+        }
+    }
+    
+    /// 列出已确认交易中出现过的所有发送方和接收方地址（不含 "System"）
+    pub fn known_addresses(&self) -> HashSet<String> {
+        let mut addresses = HashSet::new();
+        
+        for block in &self.chain {
+            for tx in &block.transactions {
+                addresses.insert(tx.sender.clone());
+                if let Some(ref recipient) = tx.recipient {
+                    addresses.insert(recipient.clone());
+                }
+            }
+        }
+        
+        addresses.remove("System");
+        addresses
+    }
+    
+    /// 按交易类型对待处理交易分组，组内保持入队顺序
+    ///
+    /// 键为类型名，例如 `"DataSubmission"`；自定义交易按标签分组，
+    /// 键为 `"Custom:<标签>"`。
+    pub fn pending_by_type(&self) -> HashMap<String, Vec<&Transaction>> {
+        let mut groups: HashMap<String, Vec<&Transaction>> = HashMap::new();
+        for tx in &self.pending_transactions {
+            let key = match &tx.transaction_type {
+                TransactionType::Custom(tag) => format!("Custom:{}", tag),
+                other => format!("{:?}", other),
+            };
+            groups.entry(key).or_default().push(tx);
+        }
+        groups
+    }
+    
+    /// 按标签查找区块，存在多个同名标签时返回最早的区块
+    pub fn find_block_by_label(&self, label: &str) -> Option<&Block> {
+        self.chain
+            .iter()
+            .find(|block| block.label.as_deref() == Some(label))
     }
     
     /// 根据交易ID查找交易
@@ -339,6 +1399,142 @@ impl Blockchain {
         None
     }
     
+    /// 按时间顺序列出指定数据ID或CID的全部已确认访问授权交易
+    pub fn data_access_history(&self, data_id: &str) -> Vec<&Transaction> {
+        let expected = format!("{}{}", DATA_ACCESS_PREFIX, data_id);
+        self.chain
+            .iter()
+            .flat_map(|block| block.transactions.iter())
+            .filter(|tx| tx.transaction_type == TransactionType::DataAccess && tx.data == expected)
+            .collect()
+    }
+    
+    /// 查找包含指定交易的已确认区块
+    pub fn block_of_transaction(&self, tx_id: &str) -> Option<&Block> {
+        self.chain
+            .iter()
+            .find(|block| block.transactions.iter().any(|tx| tx.id == tx_id))
+    }
+    
+    /// 计算交易的确认数
+    ///
+    /// 交易所在区块本身计为1个确认，之后每增加一个区块加1；
+    /// 仍在待处理队列中的交易返回 `Some(0)`，找不到的交易返回 `None`。
+    pub fn confirmations(&self, tx_id: &str) -> Option<u64> {
+        if let Some(block) = self.block_of_transaction(tx_id) {
+            let latest = self.get_latest_block()?;
+            return Some(latest.index - block.index + 1);
+        }
+        
+        if self.pending_transactions.iter().any(|tx| tx.id == tx_id) {
+            Some(0)
+        } else {
+            None
+        }
+    }
+    
+    /// 根据所需确认数判断交易的最终性状态
+    pub fn finality_status(&self, tx_id: &str, required_confirmations: u64) -> FinalityStatus {
+        match self.confirmations(tx_id) {
+            None => FinalityStatus::Unknown,
+            Some(0) => FinalityStatus::Pending,
+            Some(confirmations) if confirmations >= required_confirmations => FinalityStatus::Final,
+            Some(confirmations) => FinalityStatus::Confirming { confirmations },
+        }
+    }
+    
+    /// 截至指定高度（含）的累积校验和，用于同步时逐段比对
+    ///
+    /// 从空字符串开始，依次计算 `sha256(上一校验和 + 区块哈希)`。两个节点在
+    /// 约定高度比较校验和，即可二分定位分叉位置；高度超出链长时计算到链尾。
+    pub fn incremental_checksum(&self, up_to_index: u64) -> String {
+        self.chain
+            .iter()
+            .take_while(|block| block.index <= up_to_index)
+            .fold(String::new(), |checksum, block| {
+                crypto::hash_sha256(&format!("{}{}", checksum, block.hash))
+            })
+    }
+    
+    /// 以区块哈希为叶子的 Merkle Mountain Range 根，供轻客户端验证历史
+    pub fn mmr_root(&self) -> String {
+        bag_peaks(&self.mmr_peaks(None).0)
+    }
+    
+    /// 生成指定区块的 MMR 包含证明，区块不存在时返回 `None`
+    pub fn mmr_proof(&self, block_index: usize) -> Option<MmrProof> {
+        if block_index >= self.chain.len() {
+            return None;
+        }
+        let (peaks, siblings) = self.mmr_peaks(Some(block_index));
+        Some(MmrProof {
+            leaf_index: block_index as u64,
+            leaf_count: self.chain.len() as u64,
+            siblings,
+            peaks,
+        })
+    }
+    
+    /// 各山峰的根哈希，以及（可选）指定叶子在其山峰内的兄弟节点路径
+    fn mmr_peaks(&self, leaf: Option<usize>) -> (Vec<String>, Vec<String>) {
+        let leaves: Vec<String> = self.chain.iter().map(|block| block.hash.clone()).collect();
+        let mut peaks = Vec::new();
+        let mut path = Vec::new();
+        let mut start = 0;
+        
+        for size in mmr_peak_sizes(leaves.len() as u64) {
+            let end = start + size as usize;
+            let local = leaf.filter(|&i| (start..end).contains(&i)).map(|i| i - start);
+            let (root, peak_path) = merkle_root_and_path(&leaves[start..end], local);
+            if local.is_some() {
+                path = peak_path;
+            }
+            peaks.push(root);
+            start = end;
+        }
+        
+        (peaks, path)
+    }
+    
+    /// 导出为 GraphViz DOT 格式
+    ///
+    /// 区块为方框节点，按 `previous_hash` 指向前一个区块；带接收方的交易
+    /// 是从发送方到接收方的边，以交易类型为标签。
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph blockchain {\n    rankdir=LR;\n");
+        let index_by_hash: HashMap<&str, u64> = self
+            .chain
+            .iter()
+            .map(|block| (block.hash.as_str(), block.index))
+            .collect();
+        
+        for block in &self.chain {
+            dot.push_str(&format!(
+                "    \"block_{}\" [shape=box, label=\"#{}\\n{}\"];\n",
+                block.index,
+                block.index,
+                dot_escape(&block.hash.chars().take(12).collect::<String>())
+            ));
+            if let Some(previous) = index_by_hash.get(block.previous_hash.as_str()) {
+                dot.push_str(&format!("    \"block_{}\" -> \"block_{}\";\n", block.index, previous));
+            }
+        }
+        
+        for tx in self.chain.iter().flat_map(|block| block.transactions.iter()) {
+            if let Some(ref recipient) = tx.recipient {
+                dot.push_str(&format!(
+                    "    \"address:{}\" -> \"address:{}\" [label=\"{}\"];\n",
+                    dot_escape(&tx.sender),
+                    dot_escape(recipient),
+                    dot_escape(&format!("{:?}", tx.transaction_type))
+                ));
+            }
+        }
+        
+        dot.push_str("}\n");
+        dot
+    }
+    
     /// 序列化为JSON
     pub fn to_json(&self) -> Result<String, Box<dyn Error>> {
         let json = serde_json::to_string(self)?;
@@ -416,6 +1612,52 @@ mod tests {
         assert_eq!(tx.status, TransactionStatus::Pending);
     }
     
+    #[test]
+    fn test_multisig_threshold() {
+        let (owner_private, owner_public) = crypto::generate_keypair();
+        let (board_private, board_public) = crypto::generate_keypair();
+        let (outsider_private, _) = crypto::generate_keypair();
+        let required = vec![owner_public.clone(), board_public.clone()];
+        
+        let mut tx = Transaction::new(TransactionType::DataAccess, "owner", "授权访问数据集 ds-001")
+            .with_recipient("lab-42");
+        tx.add_signature(&owner_private).unwrap();
+        tx.add_signature(&outsider_private).unwrap();
+        
+        // 外部签名不计入阈值，重复签名只算一次
+        tx.add_signature(&owner_private).unwrap();
+        assert!(tx.verify_multisig(&required, 1));
+        assert!(!tx.verify_multisig(&required, 2));
+        
+        tx.add_signature(&board_private).unwrap();
+        assert!(tx.verify_multisig(&required, 2));
+        
+        // 签名后篡改交易内容
+        tx.data = "授权访问全部数据集".to_string();
+        assert!(!tx.verify_multisig(&required, 1));
+    }
+    
+    #[test]
+    fn test_signing_string_is_canonical() {
+        let tx = Transaction::new(TransactionType::DataSubmission, "sender123", "data")
+            .with_metadata("subject", "S001")
+            .with_metadata("session", "2");
+        
+        let mut reordered = tx.clone();
+        reordered.metadata = HashMap::new();
+        reordered.metadata.insert("session".to_string(), "2".to_string());
+        reordered.metadata.insert("subject".to_string(), "S001".to_string());
+        
+        assert_eq!(tx.to_signing_string(), reordered.to_signing_string());
+        assert!(!tx.to_signing_string().contains(' '));
+        
+        let (private_key, public_key) = crypto::generate_keypair();
+        let mut signed = tx.clone();
+        signed.sign(&private_key).unwrap();
+        reordered.signature = signed.signature.clone();
+        assert!(reordered.verify_signature(&public_key));
+    }
+    
     #[test]
     fn test_block_mining() {
         let mut block = Block::new(1, "previous_hash", Vec::new(), 2);
@@ -426,6 +1668,551 @@ mod tests {
         assert!(block.hash.starts_with("00"));
     }
     
+    #[test]
+    fn test_target_bits_mining() {
+        let block = Block::new(1, "previous_hash", Vec::new(), 0);
+        
+        let mut easy = block.clone().with_target_bits(4);
+        let mut hard = block.with_target_bits(8);
+        easy.mine();
+        hard.mine();
+        
+        // 满足8比特目标的nonce必然满足4比特目标
+        assert!(easy.nonce <= hard.nonce);
+        assert!(leading_zero_bits(&easy.hash) >= 4);
+        assert!(leading_zero_bits(&hard.hash) >= 8);
+        assert!(easy.is_valid());
+        assert!(hard.is_valid());
+        
+        // 提高目标后，原有的挖矿结果不再有效
+        let mut raised = hard.clone();
+        raised.target_bits = leading_zero_bits(&hard.hash) + 1;
+        assert!(!raised.is_valid());
+    }
+    
+    #[test]
+    fn test_leading_zero_bits() {
+        assert_eq!(leading_zero_bits("ff"), 0);
+        assert_eq!(leading_zero_bits("0f"), 4);
+        assert_eq!(leading_zero_bits("0010"), 11);
+        assert_eq!(leading_zero_bits("0000"), 16);
+    }
+    
+    #[test]
+    fn test_total_gas_fees() {
+        let transactions = vec![
+            Transaction::new(TransactionType::DataSubmission, "a", "1").with_gas_fee(21000),
+            Transaction::new(TransactionType::DataAccess, "b", "2").with_gas_fee(5000),
+            Transaction::new(TransactionType::TokenTransfer, "c", "3"),
+        ];
+        let block = Block::new(1, "previous_hash", transactions, 1);
+        assert_eq!(block.total_gas_fees(), 26000);
+        
+        let mut blockchain = Blockchain::new(1, 50);
+        blockchain.chain.push(block);
+        assert_eq!(blockchain.total_fees_collected(), 26000);
+    }
+    
+    #[test]
+    fn test_transaction_type_round_trip() {
+        let variants = vec![
+            TransactionType::DataSubmission,
+            TransactionType::DataAccess,
+            TransactionType::CredentialVerification,
+            TransactionType::TokenTransfer,
+            TransactionType::SmartContractInteraction,
+            TransactionType::Custom("foo".to_string()),
+        ];
+        
+        for variant in variants {
+            let tx = Transaction::new(variant.clone(), "sender123", "data");
+            let restored = Transaction::from_json(&tx.to_json().unwrap()).unwrap();
+            assert_eq!(restored.transaction_type, variant);
+            
+            let json = serde_json::to_string(&variant).unwrap();
+            let restored: TransactionType = serde_json::from_str(&json).unwrap();
+            assert_eq!(restored, variant);
+        }
+        
+        let json = serde_json::to_value(TransactionType::Custom("foo".to_string())).unwrap();
+        assert_eq!(json, serde_json::json!({"type": "Custom", "value": "foo"}));
+    }
+    
+    #[test]
+    fn test_legacy_transaction_type_deserialization() {
+        let tx = Transaction::new(TransactionType::DataAccess, "sender123", "data");
+        let mut json = serde_json::to_value(&tx).unwrap();
+        
+        // 旧版本的单元变体序列化为字符串
+        json["transaction_type"] = serde_json::json!("DataAccess");
+        let restored: Transaction = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(restored.transaction_type, TransactionType::DataAccess);
+        
+        // 旧版本的Custom变体序列化为外部标签对象
+        json["transaction_type"] = serde_json::json!({"Custom": "foo"});
+        let restored: Transaction = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.transaction_type, TransactionType::Custom("foo".to_string()));
+    }
+    
+    #[test]
+    fn test_confirmations() {
+        let mut blockchain = Blockchain::new(1, 50);
+        let (private_key, public_key) = crypto::generate_keypair();
+        blockchain.register_public_key("sender123", &public_key);
+        
+        let tx_id = blockchain.submit(TransactionType::DataSubmission, "sender123", "第一笔", &private_key).unwrap();
+        assert_eq!(blockchain.confirmations(&tx_id), Some(0));
+        assert!(blockchain.block_of_transaction(&tx_id).is_none());
+        
+        blockchain.mine_pending_transactions("miner").unwrap();
+        assert_eq!(blockchain.block_of_transaction(&tx_id).unwrap().index, 1);
+        assert_eq!(blockchain.confirmations(&tx_id), Some(1));
+        
+        blockchain.submit(TransactionType::DataSubmission, "sender123", "第二笔", &private_key).unwrap();
+        blockchain.mine_pending_transactions("miner").unwrap();
+        assert_eq!(blockchain.confirmations(&tx_id), Some(2));
+        
+        assert_eq!(blockchain.confirmations("unknown"), None);
+    }
+    
+    #[test]
+    fn test_finality_status() {
+        let mut blockchain = Blockchain::new(1, 50);
+        let (private_key, public_key) = crypto::generate_keypair();
+        blockchain.register_public_key("sender123", &public_key);
+        
+        let tx_id = blockchain.submit(TransactionType::DataSubmission, "sender123", "第一笔", &private_key).unwrap();
+        assert_eq!(blockchain.finality_status(&tx_id, 3), FinalityStatus::Pending);
+        
+        blockchain.mine_pending_transactions("miner").unwrap();
+        assert_eq!(blockchain.finality_status(&tx_id, 3), FinalityStatus::Confirming { confirmations: 1 });
+        
+        blockchain.mine_empty_block("miner").unwrap();
+        assert_eq!(blockchain.finality_status(&tx_id, 3), FinalityStatus::Confirming { confirmations: 2 });
+        
+        blockchain.mine_empty_block("miner").unwrap();
+        assert_eq!(blockchain.finality_status(&tx_id, 3), FinalityStatus::Final);
+        assert_eq!(blockchain.finality_status("unknown", 3), FinalityStatus::Unknown);
+        
+        let json = serde_json::to_string(&FinalityStatus::Confirming { confirmations: 2 }).unwrap();
+        assert_eq!(json, r#"{"status":"Confirming","confirmations":2}"#);
+    }
+    
+    #[test]
+    fn test_keccak_chain() {
+        let mut blockchain = Blockchain::with_hash_algorithm(1, 50, HashAlgorithm::Keccak256);
+        let (private_key, public_key) = crypto::generate_keypair();
+        blockchain.register_public_key("sender123", &public_key);
+        
+        blockchain.submit(TransactionType::DataSubmission, "sender123", "data", &private_key).unwrap();
+        let block = blockchain.mine_pending_transactions("miner").unwrap();
+        
+        assert_eq!(block.hash_algorithm, HashAlgorithm::Keccak256);
+        assert!(block.is_valid());
+        assert!(blockchain.is_chain_valid());
+        
+        // 使用SHA-256重新计算的哈希无法通过验证
+        let mut sha_block = block.clone();
+        sha_block.hash_algorithm = HashAlgorithm::Sha256;
+        assert_ne!(sha_block.calculate_hash(), block.hash);
+        assert!(!sha_block.is_valid());
+        
+        blockchain.chain[1] = sha_block;
+        assert!(!blockchain.is_chain_valid());
+    }
+    
+    #[test]
+    fn test_known_addresses() {
+        let mut blockchain = Blockchain::new(1, 50);
+        let (private_key, public_key) = crypto::generate_keypair();
+        blockchain.register_public_key("alice", &public_key);
+        
+        let mut tx = Transaction::new(TransactionType::DataAccess, "alice", "data")
+            .with_recipient("bob");
+        tx.sign(&private_key).unwrap();
+        blockchain.add_transaction(tx).unwrap();
+        
+        blockchain.mine_pending_transactions("miner").unwrap();
+        
+        // 待处理交易中的地址不计入
+        blockchain.submit(TransactionType::DataSubmission, "alice", "pending", &private_key).unwrap();
+        blockchain.register_public_key("carol", &public_key);
+        blockchain.submit(TransactionType::DataSubmission, "carol", "pending", &private_key).unwrap();
+        
+        let expected: HashSet<String> = ["alice", "bob", "miner"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(blockchain.known_addresses(), expected);
+    }
+    
+    #[test]
+    fn test_on_block_mined() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        
+        let mut blockchain = Blockchain::new(1, 50);
+        let (private_key, public_key) = crypto::generate_keypair();
+        blockchain.register_public_key("sender123", &public_key);
+        
+        let mined = Rc::new(RefCell::new(Vec::new()));
+        let observer = Rc::clone(&mined);
+        blockchain.on_block_mined(Box::new(move |block| observer.borrow_mut().push(block.index)));
+        
+        blockchain.submit(TransactionType::DataSubmission, "sender123", "第一笔", &private_key).unwrap();
+        blockchain.mine_pending_transactions("miner").unwrap();
+        blockchain.submit(TransactionType::DataSubmission, "sender123", "第二笔", &private_key).unwrap();
+        blockchain.mine_pending_transactions("miner").unwrap();
+        
+        assert_eq!(*mined.borrow(), vec![1, 2]);
+    }
+    
+    #[test]
+    fn test_resolve_fork() {
+        let mut blockchain = Blockchain::new(1, 50);
+        let (private_key, public_key) = crypto::generate_keypair();
+        blockchain.register_public_key("sender123", &public_key);
+        
+        blockchain.submit(TransactionType::DataSubmission, "sender123", "第一笔", &private_key).unwrap();
+        blockchain.mine_pending_transactions("miner").unwrap();
+        let tx_id = blockchain.submit(TransactionType::DataSubmission, "sender123", "第二笔", &private_key).unwrap();
+        blockchain.mine_pending_transactions("miner").unwrap();
+        assert_eq!(blockchain.chain.len(), 3);
+        
+        // 从区块1分叉出两个区块
+        let mut fork_a = Block::new(2, &blockchain.chain[1].hash, Vec::new(), 1);
+        fork_a.mine();
+        let mut fork_b = Block::new(3, &fork_a.hash, Vec::new(), 1);
+        fork_b.mine();
+        
+        // 工作量相同的分支不会被采用
+        let outcome = blockchain.resolve_fork(vec![fork_a.clone()]).unwrap();
+        assert_eq!(outcome, ForkOutcome { reorganized: false, rolled_back: 0 });
+        
+        let outcome = blockchain.resolve_fork(vec![fork_a, fork_b.clone()]).unwrap();
+        assert_eq!(outcome, ForkOutcome { reorganized: true, rolled_back: 1 });
+        assert_eq!(blockchain.chain.len(), 4);
+        assert_eq!(blockchain.get_latest_block().unwrap().hash, fork_b.hash);
+        assert!(blockchain.is_chain_valid());
+        
+        // 被回滚的交易回到待处理队列
+        assert_eq!(blockchain.confirmations(&tx_id), Some(0));
+        
+        let mut orphan = Block::new(5, "unknown", Vec::new(), 1);
+        orphan.mine();
+        assert!(blockchain.resolve_fork(vec![orphan]).is_err());
+    }
+    
+    #[test]
+    fn test_resolve_fork_revalidates_rolled_back_transactions() {
+        let mut blockchain = Blockchain::new(1, 50);
+        let (private_key, public_key) = crypto::generate_keypair();
+        blockchain.register_public_key("sender123", &public_key);
+        
+        let mut local = Transaction::new(TransactionType::DataSubmission, "sender123", "本地").with_idempotency_key("k1");
+        local.sign(&private_key).unwrap();
+        blockchain.add_transaction(local.clone()).unwrap();
+        blockchain.mine_pending_transactions("miner").unwrap();
+        
+        // 分支中同一发送方用相同幂等键提交了另一笔交易
+        let mut remote = Transaction::new(TransactionType::DataSubmission, "sender123", "远端").with_idempotency_key("k1");
+        remote.sign(&private_key).unwrap();
+        let genesis_hash = blockchain.chain[0].hash.clone();
+        let mut fork_a = Block::new(1, &genesis_hash, vec![remote], 1);
+        fork_a.mine();
+        let mut fork_b = Block::new(2, &fork_a.hash, Vec::new(), 1);
+        fork_b.mine();
+        
+        let outcome = blockchain.resolve_fork(vec![fork_a, fork_b]).unwrap();
+        assert_eq!(outcome, ForkOutcome { reorganized: true, rolled_back: 1 });
+        
+        // 回滚的交易与新链冲突，奖励交易也不会进入待处理队列
+        assert!(blockchain.pending_transactions.is_empty());
+        assert!(blockchain.find_transaction(&local.id).is_none());
+    }
+    
+    #[test]
+    fn test_resolve_fork_cannot_rewrite_checkpoint() {
+        let mut blockchain = Blockchain::new(1, 50);
+        blockchain.mine_empty_block("miner").unwrap();
+        blockchain.mine_empty_block("miner").unwrap();
+        blockchain.add_checkpoint(1).unwrap();
+        
+        // 从创世区块分叉、工作量更大的分支会改写检查点区块
+        let genesis_hash = blockchain.chain[0].hash.clone();
+        let mut heavy = Block::new(1, &genesis_hash, Vec::new(), 3);
+        heavy.mine();
+        assert!(blockchain.resolve_fork(vec![heavy.clone()]).is_err());
+        assert!(blockchain.replace_chain(vec![blockchain.chain[0].clone(), heavy]).is_err());
+        assert_eq!(blockchain.chain.len(), 3);
+        
+        // 检查点之后的区块仍可被替换
+        let mut after = Block::new(2, &blockchain.chain[1].hash, Vec::new(), 3);
+        after.mine();
+        assert!(blockchain.resolve_fork(vec![after.clone()]).unwrap().reorganized);
+        assert_eq!(blockchain.get_latest_block().unwrap().hash, after.hash);
+    }
+    
+    #[test]
+    fn test_total_difficulty_beats_length() {
+        let mut blockchain = Blockchain::new(1, 50);
+        let (private_key, public_key) = crypto::generate_keypair();
+        blockchain.register_public_key("sender123", &public_key);
+        
+        for data in ["第一笔", "第二笔", "第三笔"] {
+            blockchain.submit(TransactionType::DataSubmission, "sender123", data, &private_key).unwrap();
+            blockchain.mine_pending_transactions("miner").unwrap();
+        }
+        assert_eq!(blockchain.chain.len(), 4);
+        assert_eq!(blockchain.total_difficulty(), 4 * 16);
+        
+        // 只有一个高难度区块的更短链代表更多的工作量
+        let genesis = blockchain.chain[0].clone();
+        let mut heavy = Block::new(1, &genesis.hash, Vec::new(), 3);
+        heavy.mine();
+        let candidate = vec![genesis.clone(), heavy];
+        
+        assert!(blockchain.replace_chain(candidate).unwrap());
+        assert_eq!(blockchain.chain.len(), 2);
+        assert_eq!(blockchain.total_difficulty(), 16 + 4096);
+        
+        // 工作量更少的更长链不会被采用
+        let mut light = Block::new(1, &genesis.hash, Vec::new(), 1);
+        light.mine();
+        let mut light_next = Block::new(2, &light.hash, Vec::new(), 1);
+        light_next.mine();
+        assert!(!blockchain.replace_chain(vec![genesis, light, light_next]).unwrap());
+        assert_eq!(blockchain.chain.len(), 2);
+    }
+    
+    #[test]
+    fn test_checkpoint_validation() {
+        let mut blockchain = Blockchain::new(1, 50);
+        let (private_key, public_key) = crypto::generate_keypair();
+        blockchain.register_public_key("sender123", &public_key);
+        
+        for data in ["第一笔", "第二笔", "第三笔"] {
+            blockchain.submit(TransactionType::DataSubmission, "sender123", data, &private_key).unwrap();
+            blockchain.mine_pending_transactions("miner").unwrap();
+        }
+        
+        assert!(blockchain.add_checkpoint(10).is_err());
+        blockchain.add_checkpoint(3).unwrap();
+        blockchain.submit(TransactionType::DataSubmission, "sender123", "第四笔", &private_key).unwrap();
+        blockchain.mine_pending_transactions("miner").unwrap();
+        assert!(blockchain.is_chain_valid());
+        
+        // 篡改检查点之前的区块并重新计算到检查点为止的哈希，只有检查点哈希能发现
+        blockchain.chain.truncate(4);
+        blockchain.chain[1].transactions[0].id = "篡改".to_string();
+        for i in 1..=3 {
+            blockchain.chain[i].previous_hash = blockchain.chain[i - 1].hash.clone();
+            blockchain.chain[i].hash = blockchain.chain[i].calculate_hash();
+        }
+        assert!(!blockchain.is_chain_valid());
+    }
+    
+    #[test]
+    fn test_checkpoint_still_checks_hashes() {
+        let mut blockchain = Blockchain::new(1, 50);
+        for _ in 0..3 {
+            blockchain.mine_empty_block("miner").unwrap();
+        }
+        blockchain.add_checkpoint(3).unwrap();
+        assert!(blockchain.is_chain_valid());
+        
+        // 篡改检查点之前的区块但不重新计算哈希
+        blockchain.chain[1].transactions[0].id = "篡改".to_string();
+        blockchain.chain[1].transactions[0].recipient = Some("mallory".to_string());
+        assert!(!blockchain.is_chain_valid());
+        assert_eq!(blockchain.find_first_invalid_block(), Some((1, "hash mismatch".to_string())));
+    }
+    
+    #[test]
+    fn test_genesis_checkpoint() {
+        let trusted = Blockchain::new(1, 50);
+        let mut blockchain = Blockchain::new(1, 50);
+        blockchain.chain[0].timestamp += 1;
+        blockchain.chain[0].hash = blockchain.chain[0].calculate_hash();
+        blockchain.mine_empty_block("miner").unwrap();
+        assert!(blockchain.is_chain_valid());
+        
+        // 无关链的创世区块与受信任的创世检查点不符
+        blockchain.checkpoints.insert(0, trusted.chain[0].hash.clone());
+        assert!(!blockchain.is_chain_valid());
+        assert_eq!(blockchain.find_first_invalid_block(), Some((0, "checkpoint mismatch".to_string())));
+        
+        blockchain.checkpoints.insert(0, blockchain.chain[0].hash.clone());
+        assert!(blockchain.is_chain_valid());
+    }
+    
+    #[test]
+    fn test_checkpoints_not_deserialized() {
+        let mut blockchain = Blockchain::new(1, 50);
+        for _ in 0..3 {
+            blockchain.mine_empty_block("miner").unwrap();
+        }
+        blockchain.add_checkpoint(3).unwrap();
+        assert!(!blockchain.to_json().unwrap().contains("checkpoints"));
+        
+        // 对端篡改区块后在链尖注入检查点，试图跳过哈希和工作量验证
+        blockchain.chain[1].nonce += 1;
+        let mut json: serde_json::Value = serde_json::from_str(&blockchain.to_json().unwrap()).unwrap();
+        json["checkpoints"] = serde_json::json!({ "3": blockchain.chain[3].hash });
+        
+        let loaded = Blockchain::from_json(&json.to_string()).unwrap();
+        assert!(loaded.checkpoints.is_empty());
+        assert!(!loaded.is_chain_valid());
+    }
+    
+    #[test]
+    fn test_low_difficulty_block_rejected() {
+        let mut blockchain = Blockchain::new(2, 50);
+        let genesis_hash = blockchain.chain[0].hash.clone();
+        
+        // 声明难度为0的区块无需挖矿即可满足自身的目标
+        let cheap = Block::new(1, &genesis_hash, Vec::new(), 0);
+        assert!(cheap.is_valid());
+        assert!(blockchain.resolve_fork(vec![cheap.clone()]).is_err());
+        
+        let mut cheap_bits = Block::new(1, &genesis_hash, Vec::new(), 2).with_target_bits(4);
+        cheap_bits.mine();
+        assert!(cheap_bits.is_valid());
+        assert!(blockchain.resolve_fork(vec![cheap_bits]).is_err());
+        
+        blockchain.chain.push(cheap);
+        assert!(!blockchain.is_chain_valid());
+        
+        blockchain.chain.pop();
+        let mut honest = Block::new(1, &genesis_hash, Vec::new(), 2);
+        honest.mine();
+        assert!(blockchain.resolve_fork(vec![honest]).unwrap().reorganized);
+    }
+    
+    #[test]
+    fn test_mine_empty_block() {
+        let mut blockchain = Blockchain::new(1, 50);
+        assert!(blockchain.mine_pending_transactions("miner").is_err());
+        
+        let block = blockchain.mine_empty_block("miner").unwrap();
+        assert_eq!(blockchain.chain.len(), 2);
+        assert_eq!(block.transactions.len(), 1);
+        
+        let reward = &block.transactions[0];
+        assert_eq!(reward.sender, "System");
+        assert_eq!(reward.recipient.as_deref(), Some("miner"));
+        assert_eq!(reward.data, "Reward: 50");
+        assert!(blockchain.is_chain_valid());
+    }
+    
+    #[test]
+    fn test_data_access_history() {
+        let mut blockchain = Blockchain::new(1, 50);
+        let (private_key, public_key) = crypto::generate_keypair();
+        blockchain.register_public_key("owner", &public_key);
+        
+        let grant = |recipient: &str, data_id: &str| {
+            let mut tx = Transaction::data_access("owner", data_id).with_recipient(recipient);
+            tx.sign(&private_key).unwrap();
+            tx
+        };
+        
+        blockchain.add_transaction(grant("lab-a", "QmDataset1")).unwrap();
+        blockchain.add_transaction(grant("lab-b", "QmDataset2")).unwrap();
+        blockchain.mine_pending_transactions("miner").unwrap();
+        blockchain.add_transaction(grant("lab-c", "QmDataset1")).unwrap();
+        blockchain.mine_pending_transactions("miner").unwrap();
+        
+        // 未确认的授权不计入历史
+        blockchain.add_transaction(grant("lab-d", "QmDataset1")).unwrap();
+        
+        let recipients: Vec<_> = blockchain
+            .data_access_history("QmDataset1")
+            .iter()
+            .map(|tx| tx.recipient.as_deref().unwrap())
+            .collect();
+        assert_eq!(recipients, vec!["lab-a", "lab-c"]);
+        assert!(blockchain.data_access_history("QmDataset").is_empty());
+    }
+    
+    #[test]
+    fn test_genesis_exempt_from_difficulty() {
+        let mut blockchain = Blockchain::new(2, 50);
+        assert_eq!(blockchain.chain[0].nonce, 0);
+        assert!(blockchain.is_chain_valid());
+        
+        blockchain.mine_empty_block("miner").unwrap();
+        assert!(blockchain.is_chain_valid());
+        
+        // 创世区块本身仍需与其哈希一致
+        blockchain.chain[0].timestamp += 1;
+        assert!(!blockchain.is_chain_valid());
+    }
+    
+    #[test]
+    fn test_to_dot() {
+        let mut blockchain = Blockchain::new(1, 50);
+        let (private_key, public_key) = crypto::generate_keypair();
+        blockchain.register_public_key("alice", &public_key);
+        
+        let mut transfer = Transaction::new(TransactionType::TokenTransfer, "alice", "10").with_recipient("bob");
+        transfer.sign(&private_key).unwrap();
+        blockchain.add_transaction(transfer).unwrap();
+        blockchain.submit(TransactionType::DataSubmission, "alice", "无接收方", &private_key).unwrap();
+        blockchain.mine_pending_transactions("miner").unwrap();
+        
+        let dot = blockchain.to_dot();
+        assert!(dot.starts_with("digraph blockchain {"));
+        assert_eq!(dot.matches("shape=box").count(), blockchain.chain.len());
+        assert!(dot.contains("\"block_1\" -> \"block_0\";"));
+        
+        // 一笔转账加一笔挖矿奖励
+        assert_eq!(dot.matches("[label=\"TokenTransfer\"]").count(), 2);
+        assert!(dot.contains("\"address:alice\" -> \"address:bob\" [label=\"TokenTransfer\"];"));
+        assert!(dot.contains("\"address:System\" -> \"address:miner\""));
+        assert!(!dot.contains("DataSubmission"));
+        
+        assert_eq!(dot_escape("say \"hi\""), "say \\\"hi\\\"");
+        
+        // 反序列化得到的哈希和地址可能含引号、反斜杠或多字节字符
+        blockchain.chain[1].hash = "区块\"哈希\\abcdefghij".to_string();
+        blockchain.chain[1].transactions[0].recipient = Some("bob\"] evil [\\".to_string());
+        let dot = blockchain.to_dot();
+        assert!(dot.contains("label=\"#1\\n区块\\\"哈希\\\\abcdef\""));
+        assert!(dot.contains("\"address:bob\\\"] evil [\\\\\""));
+    }
+    
+    #[test]
+    fn test_audit_log() {
+        let mut blockchain = Blockchain::new(1, 50);
+        assert!(blockchain.verify_audit_log());
+        
+        let (_, public_key) = crypto::generate_keypair();
+        blockchain.register_public_key("sender123", &public_key);
+        blockchain.append_audit("set_difficulty 2");
+        blockchain.append_audit("add_checkpoint 0");
+        
+        assert_eq!(blockchain.audit_log.len(), 3);
+        assert!(blockchain.audit_log[0].action.starts_with("register_public_key sender123"));
+        assert_eq!(blockchain.audit_log[2].previous_hash, blockchain.audit_log[1].hash);
+        assert!(blockchain.verify_audit_log());
+        
+        // 篡改记录内容
+        blockchain.audit_log[1].action = "set_difficulty 0".to_string();
+        assert!(!blockchain.verify_audit_log());
+        
+        // 重新计算被篡改记录的哈希也会破坏后续记录的链接
+        blockchain.audit_log[1].hash = blockchain.audit_log[1].calculate_hash();
+        assert!(!blockchain.verify_audit_log());
+        
+        // 删除记录同样可以被发现
+        let mut truncated = Blockchain::new(1, 50);
+        truncated.append_audit("a");
+        truncated.append_audit("b");
+        truncated.audit_log.remove(0);
+        assert!(!truncated.verify_audit_log());
+    }
+    
     #[test]
     fn test_blockchain_creation() {
         let blockchain = Blockchain::new(2, 50);
@@ -435,4 +2222,452 @@ mod tests {
         assert_eq!(blockchain.chain[0].index, 0);
         assert_eq!(blockchain.chain[0].previous_hash, "0");
     }
+
+    #[test]
+    fn test_submit_transaction() {
+        let mut blockchain = Blockchain::new(1, 50);
+        let (private_key, public_key) = crypto::generate_keypair();
+        blockchain.register_public_key("sender123", &public_key);
+        
+        let tx_id = blockchain.submit(
+            TransactionType::DataSubmission,
+            "sender123",
+            "测试数据提交",
+            &private_key,
+        ).unwrap();
+        
+        // 验证交易已进入待处理队列且签名有效
+        assert_eq!(blockchain.pending_transactions.len(), 1);
+        let tx = blockchain.find_transaction(&tx_id).unwrap();
+        assert!(tx.verify_signature(&public_key));
+        assert_eq!(tx.gas_fee, Some(tx.estimate_fee(DEFAULT_BASE_FEE, DEFAULT_FEE_PER_BYTE)));
+    }
+    
+    #[test]
+    fn test_idempotency_key() {
+        let mut blockchain = Blockchain::new(1, 50);
+        let (private_key, public_key) = crypto::generate_keypair();
+        blockchain.register_public_key("sender123", &public_key);
+        
+        let mut first = Transaction::new(TransactionType::DataSubmission, "sender123", "上传")
+            .with_idempotency_key("upload-42");
+        first.sign(&private_key).unwrap();
+        blockchain.add_transaction(first).unwrap();
+        
+        // 重试产生的交易内容和时间戳不同，但幂等键相同
+        let mut retry = Transaction::new(TransactionType::DataSubmission, "sender123", "上传（重试）")
+            .with_idempotency_key("upload-42");
+        retry.sign(&private_key).unwrap();
+        assert!(blockchain.add_transaction(retry.clone()).is_err());
+        
+        // 交易被确认后仍然拒绝重复的幂等键
+        blockchain.mine_pending_transactions("miner").unwrap();
+        assert!(blockchain.add_transaction(retry).is_err());
+        
+        let mut other = Transaction::new(TransactionType::DataSubmission, "sender123", "另一次上传")
+            .with_idempotency_key("upload-43");
+        other.sign(&private_key).unwrap();
+        assert!(blockchain.add_transaction(other).is_ok());
+    }
+    
+    #[test]
+    fn test_idempotency_key_scoped_to_sender() {
+        let mut blockchain = Blockchain::new(1, 50);
+        let (alice_key, alice_public) = crypto::generate_keypair();
+        let (mallory_key, mallory_public) = crypto::generate_keypair();
+        blockchain.register_public_key("alice", &alice_public);
+        blockchain.register_public_key("mallory", &mallory_public);
+        
+        // 其他发送方抢先使用同一个幂等键，不影响原发送方的交易
+        let mut squatter = Transaction::new(TransactionType::DataSubmission, "mallory", "抢占")
+            .with_idempotency_key("upload-42");
+        squatter.sign(&mallory_key).unwrap();
+        blockchain.add_transaction(squatter).unwrap();
+        
+        let mut genuine = Transaction::new(TransactionType::DataSubmission, "alice", "上传")
+            .with_idempotency_key("upload-42");
+        genuine.sign(&alice_key).unwrap();
+        assert!(blockchain.add_transaction(genuine).is_ok());
+        assert_eq!(blockchain.pending_transactions.len(), 2);
+    }
+    
+    #[test]
+    fn test_demo_chain_is_deterministic() {
+        let first = Blockchain::demo_chain(7);
+        let second = Blockchain::demo_chain(7);
+        assert_eq!(
+            serde_json::to_string(&first).unwrap(),
+            serde_json::to_string(&second).unwrap()
+        );
+        
+        assert!(first.is_chain_valid());
+        assert!(first.verify_audit_log());
+        assert_eq!(first.chain.len(), 5);
+        assert_eq!(first.public_keys.len(), 3);
+        let submissions = first
+            .chain
+            .iter()
+            .flat_map(|block| &block.transactions)
+            .filter(|tx| tx.transaction_type == TransactionType::DataSubmission)
+            .count();
+        assert!(submissions >= 1);
+        
+        let other = Blockchain::demo_chain(8);
+        assert_ne!(other.get_latest_block().unwrap().hash, first.get_latest_block().unwrap().hash);
+    }
+    
+    #[test]
+    fn test_custom_handlers() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        
+        let mut blockchain = Blockchain::new(1, 50);
+        let granted = Rc::new(RefCell::new(Vec::new()));
+        let recorder = granted.clone();
+        blockchain.register_custom_handler("grant", Box::new(move |tx| {
+            recorder.borrow_mut().push(tx.data.clone());
+            Ok(())
+        }));
+        
+        let grant = Transaction::new(TransactionType::Custom("grant".to_string()), "sender123", "ds-001");
+        blockchain.process_custom(&grant).unwrap();
+        assert_eq!(*granted.borrow(), vec!["ds-001".to_string()]);
+        
+        let revoke = Transaction::new(TransactionType::Custom("revoke".to_string()), "sender123", "ds-001");
+        assert!(blockchain.process_custom(&revoke).is_err());
+        let access = Transaction::new(TransactionType::DataAccess, "sender123", "ds-001");
+        assert!(blockchain.process_custom(&access).is_err());
+        assert_eq!(granted.borrow().len(), 1);
+    }
+    
+    #[test]
+    fn test_test_difficulty() {
+        let mut blockchain = Blockchain::new(5, 50).with_test_difficulty();
+        assert_eq!(blockchain.difficulty, TEST_DIFFICULTY);
+        
+        for _ in 0..20 {
+            blockchain.mine_empty_block("miner").unwrap();
+        }
+        // 区块按固定的低难度挖出，而不是链原本的难度 5
+        assert!(blockchain.chain[1..]
+            .iter()
+            .all(|block| block.difficulty == TEST_DIFFICULTY && !block.use_target_bits));
+        assert!(blockchain.is_chain_valid());
+    }
+    
+    #[test]
+    fn test_incremental_checksum() {
+        let local = Blockchain::demo_chain(7);
+        let mut peer = Blockchain::demo_chain(7);
+        for height in 0..5 {
+            assert_eq!(local.incremental_checksum(height), peer.incremental_checksum(height));
+        }
+        assert_eq!(local.incremental_checksum(100), local.incremental_checksum(4));
+        
+        // 篡改高度2的区块：此前的校验和一致，此后全部不同
+        peer.chain[2].nonce += 1;
+        peer.chain[2].hash = peer.chain[2].calculate_hash();
+        for height in 0..2 {
+            assert_eq!(local.incremental_checksum(height), peer.incremental_checksum(height));
+        }
+        for height in 2..5 {
+            assert_ne!(local.incremental_checksum(height), peer.incremental_checksum(height));
+        }
+    }
+    
+    #[test]
+    fn test_mmr_proofs() {
+        let mut blockchain = Blockchain::new(1, 50);
+        // 7个区块组成大小为 4、2、1 的三座山峰
+        for _ in 0..6 {
+            blockchain.mine_empty_block("miner").unwrap();
+        }
+        let root = blockchain.mmr_root();
+        
+        for index in [0, 3, 5, 6] {
+            let proof = blockchain.mmr_proof(index).unwrap();
+            assert_eq!(proof.peaks.len(), 3);
+            assert!(proof.verify(&blockchain.chain[index].hash, &root), "block {}", index);
+            
+            // 其他区块的哈希或其他根都无法通过验证
+            assert!(!proof.verify(&blockchain.chain[(index + 1) % 7].hash, &root));
+            assert!(!proof.verify(&blockchain.chain[index].hash, &"0".repeat(64)));
+        }
+        assert!(blockchain.mmr_proof(7).is_none());
+        
+        // 新区块改变根，旧证明对新根失效
+        let old_proof = blockchain.mmr_proof(3).unwrap();
+        blockchain.mine_empty_block("miner").unwrap();
+        let new_root = blockchain.mmr_root();
+        assert_ne!(new_root, root);
+        assert!(!old_proof.verify(&blockchain.chain[3].hash, &new_root));
+        let proof = blockchain.mmr_proof(7).unwrap();
+        assert_eq!(proof.peaks.len(), 1);
+        assert!(proof.verify(&blockchain.chain[7].hash, &new_root));
+        
+        // 只有创世区块时根就是其哈希
+        let single = Blockchain::new(1, 50);
+        assert_eq!(single.mmr_root(), single.chain[0].hash);
+        assert!(single.mmr_proof(0).unwrap().verify(&single.chain[0].hash, &single.mmr_root()));
+    }
+    
+    #[test]
+    fn test_future_timestamp_rejected() {
+        let mut blockchain = Blockchain::new(1, 50).with_max_future_skew_secs(60);
+        let (private_key, public_key) = crypto::generate_keypair();
+        blockchain.register_public_key("sender123", &public_key);
+        
+        let signed_at = |offset: u64, data: &str| {
+            let mut tx = Transaction::new(TransactionType::DataSubmission, "sender123", data);
+            tx.timestamp += offset;
+            tx.sign(&private_key).unwrap();
+            tx
+        };
+        
+        assert!(blockchain.add_transaction(signed_at(3600, "未来一小时")).is_err());
+        assert!(blockchain.add_transaction(signed_at(30, "超前30秒")).is_ok());
+        assert_eq!(blockchain.pending_transactions.len(), 1);
+        assert_eq!(Blockchain::new(1, 50).max_future_skew_secs, DEFAULT_MAX_FUTURE_SKEW_SECS);
+    }
+    
+    #[test]
+    fn test_find_first_invalid_block() {
+        let mut blockchain = Blockchain::new(2, 50);
+        for _ in 0..4 {
+            blockchain.mine_empty_block("miner").unwrap();
+        }
+        assert_eq!(blockchain.find_first_invalid_block(), None);
+        let original = blockchain.chain.clone();
+        
+        // 篡改区块内容（区块哈希覆盖交易ID）
+        blockchain.chain[2].transactions[0].id = "tampered".to_string();
+        assert_eq!(blockchain.find_first_invalid_block(), Some((2, "hash mismatch".to_string())));
+        assert!(!blockchain.is_chain_valid());
+        
+        // 篡改前一区块哈希
+        blockchain.chain = original.clone();
+        blockchain.chain[3].previous_hash = "0".repeat(64);
+        assert_eq!(blockchain.find_first_invalid_block(), Some((3, "broken link".to_string())));
+        
+        // 篡改随机数
+        blockchain.chain = original.clone();
+        blockchain.chain[1].nonce += 1;
+        assert_eq!(blockchain.find_first_invalid_block(), Some((1, "hash mismatch".to_string())));
+        
+        // 篡改随机数后重新计算哈希，哈希自洽但不满足难度
+        loop {
+            blockchain.chain[1].hash = blockchain.chain[1].calculate_hash();
+            if !blockchain.chain[1].hash.starts_with("00") {
+                break;
+            }
+            blockchain.chain[1].nonce += 1;
+        }
+        assert_eq!(blockchain.find_first_invalid_block(), Some((1, "difficulty unmet".to_string())));
+        
+        blockchain.chain = original;
+        blockchain.chain[0].previous_hash = "1".to_string();
+        assert_eq!(blockchain.find_first_invalid_block(), Some((0, "invalid genesis".to_string())));
+    }
+    
+    #[test]
+    fn test_external_signing() {
+        let (private_key, public_key) = crypto::generate_keypair();
+        let mut tx = Transaction::new(TransactionType::DataSubmission, "sender123", "外部签名");
+        
+        // 模拟外部签名方：只拿到待签名字节，返回签名
+        let payload = String::from_utf8(tx.signing_bytes()).unwrap();
+        assert_eq!(payload, tx.to_signing_string());
+        let signature = crypto::sign_data(&payload, &private_key).unwrap();
+        
+        tx.apply_signature(signature);
+        assert!(tx.verify_signature(&public_key));
+        
+        // 签名后修改交易内容会使签名失效
+        tx.data = "篡改".to_string();
+        assert!(!tx.verify_signature(&public_key));
+    }
+    
+    #[test]
+    fn test_merge_mempool() {
+        let mut blockchain = Blockchain::new(1, 50);
+        let mut peer = Blockchain::new(1, 50);
+        let (private_key, public_key) = crypto::generate_keypair();
+        blockchain.register_public_key("sender123", &public_key);
+        peer.register_public_key("sender123", &public_key);
+        
+        let shared = blockchain.submit(TransactionType::DataSubmission, "sender123", "共享", &private_key).unwrap();
+        let shared_tx = blockchain.find_transaction(&shared).unwrap().clone();
+        peer.add_transaction(shared_tx).unwrap();
+        peer.submit(TransactionType::DataSubmission, "sender123", "新交易", &private_key).unwrap();
+        
+        // 未签名的交易无法通过校验
+        let mut incoming = peer.pending_transactions.clone();
+        incoming.push(Transaction::new(TransactionType::DataSubmission, "sender123", "未签名"));
+        
+        assert_eq!(blockchain.merge_mempool(&incoming), 1);
+        assert_eq!(blockchain.pending_transactions.len(), 2);
+        
+        // 再次合并不会重复加入
+        assert_eq!(blockchain.merge_mempool(&incoming), 0);
+    }
+    
+    #[test]
+    fn test_pending_by_type() {
+        let mut blockchain = Blockchain::new(1, 50);
+        let (private_key, public_key) = crypto::generate_keypair();
+        blockchain.register_public_key("sender123", &public_key);
+        
+        blockchain.submit(TransactionType::DataSubmission, "sender123", "数据1", &private_key).unwrap();
+        blockchain.submit(TransactionType::DataAccess, "sender123", "访问", &private_key).unwrap();
+        blockchain.submit(TransactionType::DataSubmission, "sender123", "数据2", &private_key).unwrap();
+        blockchain.submit(TransactionType::Custom("grant".to_string()), "sender123", "授权", &private_key).unwrap();
+        blockchain.submit(TransactionType::Custom("revoke".to_string()), "sender123", "撤销", &private_key).unwrap();
+        
+        let groups = blockchain.pending_by_type();
+        assert_eq!(groups.len(), 4);
+        assert_eq!(groups["DataSubmission"].len(), 2);
+        assert_eq!(groups["DataSubmission"][0].data, "数据1");
+        assert_eq!(groups["DataAccess"].len(), 1);
+        assert_eq!(groups["Custom:grant"].len(), 1);
+        assert_eq!(groups["Custom:revoke"].len(), 1);
+    }
+    
+    #[test]
+    fn test_throughput() {
+        let mut blockchain = Blockchain::new(1, 50);
+        assert_eq!(blockchain.average_block_time(), 0.0);
+        assert_eq!(blockchain.throughput(60), 0.0);
+        
+        for _ in 0..4 {
+            blockchain.mine_empty_block("miner").unwrap();
+        }
+        // 构造已知的时间戳：每10秒一个区块，每个区块含一笔奖励交易
+        for (i, block) in blockchain.chain.iter_mut().enumerate() {
+            block.timestamp = 1_000 + 10 * i as u64;
+        }
+        
+        assert_eq!(blockchain.average_block_time(), 10.0);
+        // 最近20秒内（时间戳 1020 之后）有两个区块
+        assert_eq!(blockchain.throughput(20), 2.0 / 20.0);
+        assert_eq!(blockchain.throughput(1_000), 4.0 / 1_000.0);
+        assert_eq!(blockchain.throughput(0), 0.0);
+    }
+    
+    #[test]
+    fn test_block_label() {
+        let mut blockchain = Blockchain::new(1, 50);
+        blockchain.mine_empty_block("miner").unwrap();
+        blockchain.mine_empty_block("miner").unwrap();
+        
+        // 默认情况下标签不参与哈希，给已挖出的区块打标签不影响链的有效性
+        let hash = blockchain.chain[1].hash.clone();
+        blockchain.chain[1].set_label("Q1 2024 data release snapshot");
+        assert_eq!(blockchain.chain[1].hash, hash);
+        assert!(blockchain.is_chain_valid());
+        
+        let found = blockchain.find_block_by_label("Q1 2024 data release snapshot").unwrap();
+        assert_eq!(found.index, 1);
+        assert!(blockchain.find_block_by_label("Q2 2024").is_none());
+        
+        // 标签参与哈希时，修改标签会改变区块哈希
+        let mut block = Block::new(1, "0", Vec::new(), 1).with_label_in_hash();
+        block.set_label("v1");
+        block.mine();
+        assert!(block.is_valid());
+        block.label = Some("v2".to_string());
+        assert!(!block.is_valid());
+    }
+    
+    #[test]
+    fn test_mempool_capacity() {
+        let mut blockchain = Blockchain::new(1, 50).with_max_mempool_size(3);
+        let (private_key, public_key) = crypto::generate_keypair();
+        blockchain.register_public_key("sender123", &public_key);
+        
+        let signed = |data: &str, fee: u64| {
+            let mut tx = Transaction::new(TransactionType::DataSubmission, "sender123", data)
+                .with_gas_fee(fee);
+            tx.sign(&private_key).unwrap();
+            tx
+        };
+        
+        for (data, fee) in [("a", 300), ("b", 100), ("c", 200)] {
+            blockchain.add_transaction(signed(data, fee)).unwrap();
+        }
+        assert_eq!(blockchain.pending_transactions.len(), 3);
+        
+        // 手续费更高的交易淘汰手续费最低的交易
+        blockchain.add_transaction(signed("d", 250)).unwrap();
+        let mut fees: Vec<u64> = blockchain.pending_transactions.iter().filter_map(|tx| tx.gas_fee).collect();
+        fees.sort();
+        assert_eq!(fees, vec![200, 250, 300]);
+        
+        // 手续费不高于最低值的交易被拒绝
+        assert!(blockchain.add_transaction(signed("e", 150)).is_err());
+        assert!(blockchain.add_transaction(signed("f", 200)).is_err());
+        assert_eq!(blockchain.pending_transactions.len(), 3);
+    }
+    
+    #[test]
+    fn test_estimate_fee() {
+        let small = Transaction::new(TransactionType::DataSubmission, "sender123", &"x".repeat(100));
+        let large = Transaction::new(TransactionType::DataSubmission, "sender123", &"x".repeat(1000));
+        
+        assert_eq!(small.estimate_fee(21000, 16), 21000 + 1600);
+        assert_eq!(large.estimate_fee(21000, 16), 21000 + 16000);
+        assert_eq!(large.estimate_fee(0, 16), 10 * small.estimate_fee(0, 16));
+    }
+    
+    #[test]
+    fn test_public_key_registry() {
+        let mut blockchain = Blockchain::new(1, 50);
+        let (private_key, public_key) = crypto::generate_keypair();
+        blockchain.register_public_key("registered", &public_key);
+        
+        let mut tx = Transaction::new(TransactionType::DataAccess, "registered", "data");
+        tx.sign(&private_key).unwrap();
+        assert!(blockchain.add_transaction(tx).is_ok());
+        
+        let mut tx = Transaction::new(TransactionType::DataAccess, "unregistered", "data");
+        tx.sign(&private_key).unwrap();
+        assert!(blockchain.add_transaction(tx).is_err());
+        
+        // 使用其他密钥签名的交易无法通过验证
+        let (other_key, _) = crypto::generate_keypair();
+        let mut tx = Transaction::new(TransactionType::DataAccess, "registered", "data");
+        tx.sign(&other_key).unwrap();
+        assert!(blockchain.add_transaction(tx).is_err());
+        
+        assert_eq!(blockchain.pending_transactions.len(), 1);
+    }
+    
+    #[test]
+    fn test_reject_external_reward_transactions() {
+        let mut blockchain = Blockchain::new(1, 50);
+        let mut forged = Transaction::new(TransactionType::TokenTransfer, "System", "Reward: 1000000")
+            .with_recipient("mallory");
+        forged.signature = Some("junk".to_string());
+        
+        assert!(blockchain.add_transaction(forged.clone()).is_err());
+        assert_eq!(blockchain.merge_mempool(&[forged.clone()]), 0);
+        assert!(blockchain.pending_transactions.is_empty());
+        
+        // 区块只能包含一笔金额正确的奖励交易
+        let block = blockchain.mine_empty_block("miner").unwrap();
+        let previous = blockchain.chain[0].clone();
+        
+        let mut inflated = block.clone();
+        inflated.transactions[0].data = "Reward: 1000000".to_string();
+        inflated.hash = inflated.calculate_hash();
+        inflated.mine();
+        assert_eq!(blockchain.new_block_error(&inflated, &previous), Some("invalid reward"));
+        
+        let mut doubled = block.clone();
+        doubled.transactions.push(block.transactions[0].clone());
+        doubled.hash = doubled.calculate_hash();
+        doubled.mine();
+        assert_eq!(blockchain.new_block_error(&doubled, &previous), Some("invalid reward"));
+        
+        assert_eq!(blockchain.new_block_error(&block, &previous), None);
+    }
 } 
\ No newline at end of file
@@ -1,7 +1,118 @@
 use sha2::{Sha256, Digest};
+use sha3::{Keccak256, Sha3_256};
+use aes_gcm::{Aes256Gcm, Nonce};
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::aead::generic_array::GenericArray;
+use aes_gcm::aead::stream::{DecryptorBE32, EncryptorBE32};
+use argon2::Argon2;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
 use rand::{Rng, thread_rng};
 use hex;
+use serde::{Serialize, Deserialize};
 use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+/// Errors produced by the structured signing and streaming encryption helpers
+#[derive(Debug)]
+pub enum CryptoError {
+    /// The value could not be serialized to canonical JSON
+    Serialization(String),
+    /// The signature could not be produced
+    Signing(String),
+    /// Streaming encryption failed
+    Encryption(String),
+    /// Streaming decryption failed, including authentication failures
+    Decryption(String),
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptoError::Serialization(msg) => write!(f, "Serialization error: {}", msg),
+            CryptoError::Signing(msg) => write!(f, "Signing error: {}", msg),
+            CryptoError::Encryption(msg) => write!(f, "Encryption error: {}", msg),
+            CryptoError::Decryption(msg) => write!(f, "Decryption error: {}", msg),
+        }
+    }
+}
+
+impl Error for CryptoError {}
+
+/// Byte buffer for decrypted content that is zeroed when cleared or dropped
+pub struct SecureBuffer {
+    bytes: Vec<u8>,
+}
+
+impl SecureBuffer {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        SecureBuffer { bytes }
+    }
+    
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes
+    }
+    
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+    
+    /// Overwrite the contents with zeros and empty the buffer, keeping its allocation
+    pub fn clear(&mut self) {
+        // Going through `as_mut_ptr` avoids creating references to the contents
+        let ptr = self.bytes.as_mut_ptr();
+        for i in 0..self.bytes.len() {
+            // Volatile writes so the zeroing is not optimized away as a dead store
+            unsafe { std::ptr::write_volatile(ptr.add(i), 0) };
+        }
+        std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+        self.bytes.clear();
+    }
+}
+
+impl std::ops::Deref for SecureBuffer {
+    type Target = [u8];
+    
+    fn deref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl Drop for SecureBuffer {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl fmt::Debug for SecureBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecureBuffer({} bytes)", self.bytes.len())
+    }
+}
+
+/// Hash algorithms available for chain hashing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Keccak256,
+    Sha3_256,
+}
+
+impl HashAlgorithm {
+    /// Hash a string with this algorithm and return the hex representation
+    pub fn hash(&self, data: &str) -> String {
+        match self {
+            HashAlgorithm::Sha256 => hash_sha256(data),
+            HashAlgorithm::Keccak256 => hash_keccak256(data),
+            HashAlgorithm::Sha3_256 => hash_sha3_256(data),
+        }
+    }
+}
 
 /// Hash a string using SHA-256 and return the hex representation
 pub fn hash_sha256(data: &str) -> String {
@@ -11,6 +122,66 @@ pub fn hash_sha256(data: &str) -> String {
     hex::encode(result)
 }
 
+/// Hash a string using Keccak-256 (as used by Ethereum) and return the hex representation
+pub fn hash_keccak256(data: &str) -> String {
+    let mut hasher = Keccak256::new();
+    hasher.update(data.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Hash a string using SHA3-256 and return the hex representation
+pub fn hash_sha3_256(data: &str) -> String {
+    let mut hasher = Sha3_256::new();
+    hasher.update(data.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Serialize a value as canonical JSON: object keys sorted, no whitespace
+///
+/// Logically equal values always produce the same string, regardless of the
+/// order in which map entries were inserted, so the output is safe to hash or sign.
+pub fn canonical_json<T: Serialize>(value: &T) -> Result<String, Box<dyn Error>> {
+    let value = serde_json::to_value(value)?;
+    Ok(canonical_json_value(&value))
+}
+
+/// Canonical JSON for an already-built `serde_json::Value`
+pub fn canonical_json_value(value: &serde_json::Value) -> String {
+    let mut out = String::new();
+    write_canonical(value, &mut out);
+    out
+}
+
+fn write_canonical(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], out);
+            }
+            out.push('}');
+        }
+        serde_json::Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
 /// Generate a random key for encryption
 pub fn generate_key() -> String {
     let key: [u8; 32] = thread_rng().gen();
@@ -44,28 +215,234 @@ pub fn decrypt(encrypted_data: &str, key: &str) -> Result<String, Box<dyn Error>
     String::from_utf8(decrypted).map_err(|e| e.into())
 }
 
-/// Generate a key pair for asymmetric encryption
-/// This is a placeholder and would be replaced with actual crypto in production
+/// Plaintext bytes per chunk in `encrypt_stream`
+const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Size of the AES-GCM authentication tag appended to each chunk
+const STREAM_TAG_SIZE: usize = 16;
+
+/// Size of the random nonce prefix written at the start of a stream
+/// (the 96-bit AES-GCM nonce minus the STREAM counter and last-chunk flag)
+const STREAM_NONCE_PREFIX_SIZE: usize = 7;
+
+/// Encrypt a stream with AES-256-GCM in fixed-size chunks (the STREAM construction)
+///
+/// The output is the random nonce prefix followed by each encrypted chunk.
+/// Each chunk is authenticated with its position and whether it is the last,
+/// so reordered, dropped or truncated chunks fail to decrypt. Memory use is
+/// bounded by two chunks regardless of the input size.
+pub fn encrypt_stream(mut reader: impl Read, key: &str, mut writer: impl Write) -> Result<(), CryptoError> {
+    let cipher = stream_cipher(key).map_err(CryptoError::Encryption)?;
+    let nonce_prefix: [u8; STREAM_NONCE_PREFIX_SIZE] = thread_rng().gen();
+    let mut encryptor = EncryptorBE32::from_aead(cipher, GenericArray::from_slice(&nonce_prefix));
+    let io_error = |e: io::Error| CryptoError::Encryption(e.to_string());
+    let aead_error = |_| CryptoError::Encryption("AES-GCM encryption failed".to_string());
+    
+    writer.write_all(&nonce_prefix).map_err(io_error)?;
+    let mut chunk = vec![0u8; STREAM_CHUNK_SIZE];
+    let mut next = vec![0u8; STREAM_CHUNK_SIZE];
+    let mut filled = read_full(&mut reader, &mut chunk).map_err(io_error)?;
+    loop {
+        let next_filled = if filled == chunk.len() {
+            read_full(&mut reader, &mut next).map_err(io_error)?
+        } else {
+            0
+        };
+        if next_filled == 0 {
+            let ciphertext = encryptor.encrypt_last(&chunk[..filled]).map_err(aead_error)?;
+            writer.write_all(&ciphertext).map_err(io_error)?;
+            break;
+        }
+        
+        let ciphertext = encryptor.encrypt_next(&chunk[..filled]).map_err(aead_error)?;
+        writer.write_all(&ciphertext).map_err(io_error)?;
+        std::mem::swap(&mut chunk, &mut next);
+        filled = next_filled;
+    }
+    
+    writer.flush().map_err(io_error)
+}
+
+/// Decrypt a stream produced by `encrypt_stream`, writing plaintext chunk by chunk
+///
+/// Plaintext is written as each chunk authenticates, so on error the writer
+/// may already hold a verified prefix of the data that should be discarded.
+pub fn decrypt_stream(mut reader: impl Read, key: &str, mut writer: impl Write) -> Result<(), CryptoError> {
+    let cipher = stream_cipher(key).map_err(CryptoError::Decryption)?;
+    let io_error = |e: io::Error| CryptoError::Decryption(e.to_string());
+    let aead_error = |_| CryptoError::Decryption("AES-GCM decryption failed: wrong key or corrupted data".to_string());
+    
+    let mut nonce_prefix = [0u8; STREAM_NONCE_PREFIX_SIZE];
+    reader
+        .read_exact(&mut nonce_prefix)
+        .map_err(|_| CryptoError::Decryption("Encrypted stream is too short".to_string()))?;
+    let mut decryptor = DecryptorBE32::from_aead(cipher, GenericArray::from_slice(&nonce_prefix));
+    
+    let chunk_len = STREAM_CHUNK_SIZE + STREAM_TAG_SIZE;
+    let mut chunk = vec![0u8; chunk_len];
+    let mut next = vec![0u8; chunk_len];
+    let mut filled = read_full(&mut reader, &mut chunk).map_err(io_error)?;
+    loop {
+        let next_filled = if filled == chunk_len {
+            read_full(&mut reader, &mut next).map_err(io_error)?
+        } else {
+            0
+        };
+        if next_filled == 0 {
+            let plaintext = decryptor.decrypt_last(&chunk[..filled]).map_err(aead_error)?;
+            writer.write_all(&plaintext).map_err(io_error)?;
+            break;
+        }
+        
+        let plaintext = decryptor.decrypt_next(&chunk[..filled]).map_err(aead_error)?;
+        writer.write_all(&plaintext).map_err(io_error)?;
+        std::mem::swap(&mut chunk, &mut next);
+        filled = next_filled;
+    }
+    
+    writer.flush().map_err(io_error)
+}
+
+/// Build the AES-256-GCM cipher for a hex key
+fn stream_cipher(key: &str) -> Result<Aes256Gcm, String> {
+    let key_bytes = hex::decode(key).map_err(|e| e.to_string())?;
+    Aes256Gcm::new_from_slice(&key_bytes).map_err(|_| "AES-256 key must be 32 bytes".to_string())
+}
+
+/// Read until `buf` is full or the reader is exhausted, returning the bytes read
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Generate a random salt for password-based key derivation
+pub fn generate_salt() -> String {
+    let salt: [u8; 16] = thread_rng().gen();
+    hex::encode(salt)
+}
+
+/// Derive a 256-bit encryption key from a password and hex salt using Argon2id
+pub fn derive_key(password: &str, salt: &str) -> Result<String, Box<dyn Error>> {
+    let salt_bytes = hex::decode(salt)?;
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), &salt_bytes, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(hex::encode(key))
+}
+
+/// Encrypt data with AES-256-GCM
+/// The output is the hex-encoded random 96-bit nonce followed by the ciphertext
+pub fn encrypt_aes_gcm(data: &str, key: &str) -> Result<String, Box<dyn Error>> {
+    let key_bytes = hex::decode(key)?;
+    let cipher = Aes256Gcm::new_from_slice(&key_bytes).map_err(|_| "AES-256 key must be 32 bytes")?;
+    
+    let nonce_bytes: [u8; 12] = thread_rng().gen();
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce_bytes), data.as_bytes())
+        .map_err(|_| "AES-GCM encryption failed")?;
+    
+    let mut output = nonce_bytes.to_vec();
+    output.extend(ciphertext);
+    Ok(hex::encode(output))
+}
+
+/// Decrypt data produced by `encrypt_aes_gcm`
+pub fn decrypt_aes_gcm(encrypted_data: &str, key: &str) -> Result<String, Box<dyn Error>> {
+    String::from_utf8(decrypt_aes_gcm_bytes(encrypted_data, key)?).map_err(|e| e.into())
+}
+
+/// Decrypt data produced by `encrypt_aes_gcm` into a buffer that is zeroed on drop
+pub fn decrypt_aes_gcm_secure(encrypted_data: &str, key: &str) -> Result<SecureBuffer, Box<dyn Error>> {
+    Ok(SecureBuffer::new(decrypt_aes_gcm_bytes(encrypted_data, key)?))
+}
+
+fn decrypt_aes_gcm_bytes(encrypted_data: &str, key: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let key_bytes = hex::decode(key)?;
+    let cipher = Aes256Gcm::new_from_slice(&key_bytes).map_err(|_| "AES-256 key must be 32 bytes")?;
+    
+    let data_bytes = hex::decode(encrypted_data)?;
+    if data_bytes.len() < 12 {
+        return Err("Encrypted data is too short".into());
+    }
+    let (nonce_bytes, ciphertext) = data_bytes.split_at(12);
+    let decrypted = cipher
+        .decrypt(Nonce::from_slice(nonce_bytes), ciphertext)
+        .map_err(|_| "AES-GCM decryption failed: wrong key or corrupted data")?;
+    
+    Ok(decrypted)
+}
+
+/// Generate an Ed25519 key pair as hex strings
 pub fn generate_keypair() -> (String, String) {
     let private_key = generate_key();
-    let public_key = hash_sha256(&private_key)[..40].to_string();
+    let public_key = derive_public_key(&private_key);
     (private_key, public_key)
 }
 
-/// Sign data with a private key
-/// This is a placeholder and would be replaced with actual crypto in production
+/// Derive a key pair deterministically from seed bytes, for reproducible demos and tests
+pub fn keypair_from_seed(seed: &[u8]) -> (String, String) {
+    let private_key = hex::encode(Sha256::digest([b"neuradesci-keypair:".as_slice(), seed].concat()));
+    let public_key = derive_public_key(&private_key);
+    (private_key, public_key)
+}
+
+/// Ed25519 signing key for a private key string
+///
+/// The key seed is the SHA-256 of the string, so any string can serve as a
+/// private key, not only the 32-byte hex keys `generate_keypair` produces.
+fn signing_key(private_key: &str) -> SigningKey {
+    SigningKey::from_bytes(&Sha256::digest(private_key.as_bytes()).into())
+}
+
+/// Derive the hex-encoded Ed25519 public key belonging to a private key
+pub fn derive_public_key(private_key: &str) -> String {
+    hex::encode(signing_key(private_key).verifying_key().as_bytes())
+}
+
+/// Sign data with a private key, returning a hex-encoded Ed25519 signature
 pub fn sign_data(data: &str, private_key: &str) -> Result<String, Box<dyn Error>> {
-    let message = format!("{}:{}", data, private_key);
-    Ok(hash_sha256(&message))
+    let signature = signing_key(private_key).sign(data.as_bytes());
+    Ok(hex::encode(signature.to_bytes()))
 }
 
-/// Verify a signature against a public key
-/// This is a placeholder and would be replaced with actual crypto in production
+/// Verify a signature produced by `sign_data` against a hex-encoded public key
 pub fn verify_signature(data: &str, signature: &str, public_key: &str) -> bool {
-    // This is simplified for demonstration
-    // In a real application, use proper signature verification
-    let derived_public = &hash_sha256(signature)[..40];
-    derived_public == public_key
+    let public_key: [u8; PUBLIC_KEY_LENGTH] = match hex::decode(public_key).ok().and_then(|b| b.try_into().ok()) {
+        Some(bytes) => bytes,
+        None => return false,
+    };
+    let signature: [u8; SIGNATURE_LENGTH] = match hex::decode(signature).ok().and_then(|b| b.try_into().ok()) {
+        Some(bytes) => bytes,
+        None => return false,
+    };
+    
+    match VerifyingKey::from_bytes(&public_key) {
+        Ok(key) => key.verify_strict(data.as_bytes(), &Signature::from_bytes(&signature)).is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Sign any serializable value over its canonical JSON form
+pub fn sign_json<T: Serialize>(value: &T, private_key: &str) -> Result<String, CryptoError> {
+    let message = canonical_json(value).map_err(|e| CryptoError::Serialization(e.to_string()))?;
+    sign_data(&message, private_key).map_err(|e| CryptoError::Signing(e.to_string()))
+}
+
+/// Verify a signature produced by `sign_json`
+pub fn verify_json<T: Serialize>(value: &T, signature: &str, public_key: &str) -> bool {
+    match canonical_json(value) {
+        Ok(message) => verify_signature(&message, signature, public_key),
+        Err(_) => false,
+    }
 }
 
 #[cfg(test)]
@@ -78,6 +455,52 @@ mod tests {
         assert_eq!(result.len(), 64);
     }
 
+    #[test]
+    fn test_hash_algorithms() {
+        // Keccak-256 of the empty string, as used by Ethereum
+        assert_eq!(
+            HashAlgorithm::Keccak256.hash(""),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            HashAlgorithm::Sha3_256.hash(""),
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"
+        );
+        assert_eq!(HashAlgorithm::Sha256.hash("test data"), hash_sha256("test data"));
+    }
+
+    #[test]
+    fn test_canonical_json() {
+        let a: serde_json::Value = serde_json::from_str(r#"{"b": 1, "a": {"d": [1, 2], "c": null}}"#).unwrap();
+        let b: serde_json::Value = serde_json::from_str(r#"{"a":{"c":null,"d":[1,2]},"b":1}"#).unwrap();
+
+        assert_eq!(canonical_json(&a).unwrap(), r#"{"a":{"c":null,"d":[1,2]},"b":1}"#);
+        assert_eq!(canonical_json(&a).unwrap(), canonical_json(&b).unwrap());
+    }
+
+    #[derive(Serialize)]
+    struct ContractCall {
+        contract: String,
+        method: String,
+        args: Vec<u64>,
+    }
+
+    #[test]
+    fn test_sign_json() {
+        let (private_key, public_key) = generate_keypair();
+        let mut call = ContractCall {
+            contract: "0xDataRegistry".to_string(),
+            method: "grantAccess".to_string(),
+            args: vec![42, 7],
+        };
+
+        let signature = sign_json(&call, &private_key).unwrap();
+        assert!(verify_json(&call, &signature, &public_key));
+
+        call.args[1] = 8;
+        assert!(!verify_json(&call, &signature, &public_key));
+    }
+
     #[test]
     fn test_encrypt_decrypt() {
         let data = "This is a test message for the NeuraDeSci platform";
@@ -89,11 +512,93 @@ mod tests {
         assert_eq!(data, decrypted);
     }
 
+    #[test]
+    fn test_aes_gcm_with_derived_key() {
+        let data = "{\"subject\": \"S001\"}";
+        let salt = generate_salt();
+        let key = derive_key("correct horse battery staple", &salt).unwrap();
+        assert_eq!(key, derive_key("correct horse battery staple", &salt).unwrap());
+
+        let encrypted = encrypt_aes_gcm(data, &key).unwrap();
+        assert_eq!(decrypt_aes_gcm(&encrypted, &key).unwrap(), data);
+
+        let wrong_key = derive_key("wrong password", &salt).unwrap();
+        assert!(decrypt_aes_gcm(&encrypted, &wrong_key).is_err());
+    }
+
+    #[test]
+    fn test_keypair_from_seed() {
+        let (private_key, public_key) = keypair_from_seed(b"seed-1");
+        assert_eq!(keypair_from_seed(b"seed-1"), (private_key.clone(), public_key.clone()));
+        assert_ne!(keypair_from_seed(b"seed-2").0, private_key);
+        assert_eq!(private_key.len(), 64);
+        
+        let signature = sign_data("payload", &private_key).unwrap();
+        assert!(verify_signature("payload", &signature, &public_key));
+    }
+    
+    #[test]
+    fn test_secure_buffer() {
+        let key = generate_key();
+        let encrypted = encrypt_aes_gcm("patient S001: epilepsy", &key).unwrap();
+        let buffer = decrypt_aes_gcm_secure(&encrypted, &key).unwrap();
+        assert_eq!(&buffer[..], b"patient S001: epilepsy");
+        assert_eq!(format!("{:?}", buffer), "SecureBuffer(22 bytes)");
+        drop(buffer);
+        
+        // The allocation is kept by clear(), so the old bytes can still be inspected.
+        // `Vec::as_ptr` does not create a reference, so the pointer stays valid.
+        let bytes = b"patient S001: epilepsy".to_vec();
+        let ptr = bytes.as_ptr();
+        let len = bytes.len();
+        let mut buffer = SecureBuffer::new(bytes);
+        buffer.clear();
+        assert!(buffer.is_empty());
+        let old_bytes = unsafe { std::slice::from_raw_parts(ptr, len) };
+        assert!(old_bytes.iter().all(|&byte| byte == 0));
+        
+        drop(buffer);
+        assert!(decrypt_aes_gcm_secure(&encrypted, &generate_key()).is_err());
+    }
+    
+    #[test]
+    fn test_stream_round_trip() {
+        let key = derive_key("correct horse battery staple", &generate_salt()).unwrap();
+        
+        // Cover a partial final chunk, an exact multiple of the chunk size and empty input
+        for len in [STREAM_CHUNK_SIZE * 3 + 1234, STREAM_CHUNK_SIZE * 2, 0] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 31 % 251) as u8).collect();
+            
+            let mut encrypted = Vec::new();
+            encrypt_stream(data.as_slice(), &key, &mut encrypted).unwrap();
+            let chunks = len.div_ceil(STREAM_CHUNK_SIZE).max(1);
+            assert_eq!(encrypted.len(), STREAM_NONCE_PREFIX_SIZE + len + chunks * STREAM_TAG_SIZE);
+            
+            let mut decrypted = Vec::new();
+            decrypt_stream(encrypted.as_slice(), &key, &mut decrypted).unwrap();
+            assert_eq!(decrypted, data);
+        }
+        
+        let data = vec![7u8; STREAM_CHUNK_SIZE * 2 + 10];
+        let mut encrypted = Vec::new();
+        encrypt_stream(data.as_slice(), &key, &mut encrypted).unwrap();
+        
+        // Dropping the final chunk must not decrypt as a shorter valid stream
+        let truncated = &encrypted[..STREAM_NONCE_PREFIX_SIZE + 2 * (STREAM_CHUNK_SIZE + STREAM_TAG_SIZE)];
+        assert!(decrypt_stream(truncated, &key, &mut Vec::new()).is_err());
+        
+        let wrong_key = derive_key("wrong password", &generate_salt()).unwrap();
+        assert!(matches!(
+            decrypt_stream(encrypted.as_slice(), &wrong_key, &mut Vec::new()),
+            Err(CryptoError::Decryption(_))
+        ));
+    }
+
     #[test]
     fn test_keypair_generation() {
         let (private_key, public_key) = generate_keypair();
         assert_eq!(private_key.len(), 64);
-        assert_eq!(public_key.len(), 40);
+        assert_eq!(public_key.len(), 64);
     }
 
     #[test]
@@ -102,6 +607,30 @@ mod tests {
         let (private_key, _) = generate_keypair();
         
         let signature = sign_data(data, &private_key).unwrap();
-        assert_eq!(signature.len(), 64);
+        assert_eq!(signature.len(), 128);
+    }
+
+    #[test]
+    fn test_signature_bound_to_data_and_key() {
+        let (private_key, public_key) = generate_keypair();
+        let (other_private_key, _) = generate_keypair();
+        let message_a = "grant access to dataset A";
+        let message_b = "grant access to dataset B";
+
+        let signature = sign_data(message_a, &private_key).unwrap();
+        assert!(verify_signature(message_a, &signature, &public_key));
+        assert!(!verify_signature(message_b, &signature, &public_key));
+
+        // Signed with a different private key
+        let forged = sign_data(message_a, &other_private_key).unwrap();
+        assert!(!verify_signature(message_a, &forged, &public_key));
+
+        // A hash over the data and public key alone is not a signature
+        let from_public_key = hash_sha256(&format!("{}:{}", message_a, public_key));
+        assert!(!verify_signature(message_a, &from_public_key, &public_key));
+        assert!(!verify_signature(message_a, "", &public_key));
+
+        // The private key itself is not a signature
+        assert!(!verify_signature(message_a, &private_key, &public_key));
     }
 } 
\ No newline at end of file
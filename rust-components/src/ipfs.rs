@@ -1,5 +1,41 @@
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::fmt;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use futures::stream::{self, StreamExt};
+
+use crate::crypto::{self, SecureBuffer};
+
+/// Chunk size used when streaming content from a reader
+const UPLOAD_CHUNK_SIZE: usize = 256 * 1024;
+
+/// Maximum number of gateway requests `get_many` keeps in flight
+const MAX_CONCURRENT_FETCHES: usize = 8;
+
+/// Errors returned for individual items of a batch fetch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IpfsError {
+    /// The identifier is not a recognized CID
+    InvalidCid(String),
+    /// The gateway request failed
+    Fetch { cid: String, reason: String },
+}
+
+impl fmt::Display for IpfsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IpfsError::InvalidCid(cid) => write!(f, "Invalid CID format: {}", cid),
+            IpfsError::Fetch { cid, reason } => write!(f, "Failed to fetch {}: {}", cid, reason),
+        }
+    }
+}
+
+impl Error for IpfsError {}
 
 /// Represents metadata for content stored on IPFS
 #[derive(Debug, Serialize, Deserialize)]
@@ -11,12 +47,160 @@ pub struct IPFSMetadata {
     pub encrypted: bool,
     pub encryption_algorithm: Option<String>,
     pub tags: Vec<String>,
+    /// Creator's signature over the other fields, set by `sign`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// An inconsistency between metadata and the content it describes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetadataIssue {
+    /// `size` does not match the content length
+    SizeMismatch { declared: usize, actual: usize },
+    /// `encrypted` is set but no `encryption_algorithm` is given
+    MissingEncryptionAlgorithm,
+    /// An `encryption_algorithm` is given but `encrypted` is not set
+    UnexpectedEncryptionAlgorithm(String),
+}
+
+impl IPFSMetadata {
+    /// Check the metadata against the content it describes
+    ///
+    /// Returns every issue found; an empty list means the metadata is consistent.
+    pub fn validate_against(&self, content: &[u8]) -> Vec<MetadataIssue> {
+        let mut issues = Vec::new();
+        
+        if self.size != content.len() {
+            issues.push(MetadataIssue::SizeMismatch { declared: self.size, actual: content.len() });
+        }
+        
+        match (self.encrypted, &self.encryption_algorithm) {
+            (true, None) => issues.push(MetadataIssue::MissingEncryptionAlgorithm),
+            (false, Some(algorithm)) => {
+                issues.push(MetadataIssue::UnexpectedEncryptionAlgorithm(algorithm.clone()))
+            }
+            _ => {}
+        }
+        
+        issues
+    }
+    
+    /// Sign the metadata, replacing any existing signature
+    ///
+    /// The signature covers the canonical JSON of every field except
+    /// `signature` itself.
+    pub fn sign(&mut self, private_key: &str) -> Result<(), Box<dyn Error>> {
+        let signature = crypto::sign_data(&self.signing_message()?, private_key)?;
+        self.signature = Some(signature);
+        Ok(())
+    }
+    
+    /// Check that the metadata was signed by `public_key` and not modified since
+    pub fn verify(&self, public_key: &str) -> bool {
+        match (&self.signature, self.signing_message()) {
+            (Some(signature), Ok(message)) => crypto::verify_signature(&message, signature, public_key),
+            _ => false,
+        }
+    }
+    
+    /// Canonical JSON of the metadata without its signature
+    fn signing_message(&self) -> Result<String, Box<dyn Error>> {
+        let mut value = serde_json::to_value(self)?;
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("signature");
+        }
+        Ok(crypto::canonical_json_value(&value))
+    }
+}
+
+/// Progress of a resumable upload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploadProgress {
+    /// Total bytes received so far; persist this and pass it back as the resume offset
+    pub bytes_uploaded: u64,
+    /// The CID, available once `metadata.size` bytes have been uploaded
+    pub cid: Option<String>,
+}
+
+/// IPNS content cached from a gateway fetch
+#[derive(Debug, Clone)]
+struct CachedContent {
+    /// ETag returned by the gateway, used to revalidate the content
+    etag: String,
+    content: Vec<u8>,
+}
+
+/// Token-bucket limiter for outgoing requests
+///
+/// The bucket holds a single token refilled at `rate` per second, so requests
+/// are spaced at least `1 / rate` seconds apart.
+#[derive(Debug)]
+struct RateLimiter {
+    rate: f64,
+    /// Available tokens and when they were last refilled; negative while callers wait
+    state: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    fn new(rate: f64) -> Self {
+        RateLimiter { rate, state: Mutex::new((1.0, Instant::now())) }
+    }
+
+    /// Take a token, sleeping until it becomes available
+    ///
+    /// The token is reserved before sleeping, so concurrent callers queue up
+    /// behind each other instead of all waking at once.
+    async fn acquire(&self) -> Result<(), Box<dyn Error>> {
+        let wait = {
+            let mut state = self.state.lock().map_err(|_| "Rate limiter poisoned")?;
+            let now = Instant::now();
+            let refilled = state.0 + now.duration_since(state.1).as_secs_f64() * self.rate;
+            *state = (refilled.min(1.0) - 1.0, now);
+            Duration::from_secs_f64((-state.0).max(0.0) / self.rate)
+        };
+        
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        Ok(())
+    }
+}
+
+/// Multihash code for SHA2-256
+const MULTIHASH_SHA2_256: u8 = 0x12;
+/// Multihash code for BLAKE3
+const MULTIHASH_BLAKE3: u8 = 0x1e;
+/// Multicodec code for raw binary content
+const CODEC_RAW: u8 = 0x55;
+/// Multicodec code for DAG-PB
+const CODEC_DAG_PB: u8 = 0x70;
+
+/// Hash function used to compute content identifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HashFn {
+    /// SHA2-256, encoded as a CIDv0 (`Qm...`)
+    #[default]
+    Sha256,
+    /// BLAKE3, encoded as a base32 CIDv1 with the raw codec (`bafk...`)
+    Blake3,
 }
 
 /// Represents a connection to an IPFS node
 pub struct IPFSClient {
     api_url: String,
     gateway_url: String,
+    /// Partially uploaded content, keyed by metadata name
+    uploads: Mutex<HashMap<String, Vec<u8>>>,
+    http: reqwest::Client,
+    /// IPNS gateway responses that carried an ETag, keyed by request path
+    cache: Mutex<HashMap<String, CachedContent>>,
+    hash_fn: HashFn,
+    /// CIDs pinned through this client (mock pin set)
+    pins: Mutex<HashSet<String>>,
+    /// Query the node at `api_url` for pin status instead of the mock pin set
+    use_remote_api: bool,
+    /// Spacing applied to async requests sent to the gateway or node
+    rate_limiter: Option<RateLimiter>,
 }
 
 impl IPFSClient {
@@ -24,6 +208,63 @@ impl IPFSClient {
         IPFSClient {
             api_url: api_url.to_string(),
             gateway_url: gateway_url.to_string(),
+            uploads: Mutex::new(HashMap::new()),
+            http: reqwest::Client::new(),
+            cache: Mutex::new(HashMap::new()),
+            hash_fn: HashFn::default(),
+            pins: Mutex::new(HashSet::new()),
+            use_remote_api: false,
+            rate_limiter: None,
+        }
+    }
+
+    /// Query the IPFS node's HTTP API where supported, instead of the in-memory mock
+    pub fn with_remote_api(mut self) -> Self {
+        self.use_remote_api = true;
+        self
+    }
+
+    /// Limit async requests to `requests_per_second`, delaying any that exceed it
+    ///
+    /// Applies to gateway fetches and node API queries. Non-positive rates
+    /// disable the limit.
+    pub fn with_rate_limit(mut self, requests_per_second: f64) -> Self {
+        self.rate_limiter = (requests_per_second > 0.0).then(|| RateLimiter::new(requests_per_second));
+        self
+    }
+
+    /// Wait for the rate limiter, if one is configured
+    async fn throttle(&self) -> Result<(), Box<dyn Error>> {
+        match &self.rate_limiter {
+            Some(limiter) => limiter.acquire().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Use the given hash function when computing CIDs
+    pub fn with_hash_fn(mut self, hash_fn: HashFn) -> Self {
+        self.hash_fn = hash_fn;
+        self
+    }
+
+    /// Compute the CID of some content using the configured hash function
+    ///
+    /// This hashes the raw bytes directly rather than building a UnixFS DAG,
+    /// so it matches real IPFS CIDs only for content added with `--raw-leaves`
+    /// in a single block.
+    pub fn compute_cid(&self, content: &[u8]) -> String {
+        cid_for(self.hash_fn, content)
+    }
+
+    /// Check fetched content against the CID it was requested by
+    ///
+    /// The hash function is taken from the CID itself, so this works for
+    /// CIDs produced under either `HashFn`.
+    pub fn verify_content(&self, cid: &str, content: &[u8]) -> bool {
+        match parse_multihash_code(cid) {
+            Some(MULTIHASH_SHA2_256) => cid_for(HashFn::Sha256, content) == cid,
+            Some(MULTIHASH_BLAKE3) => cid_for(HashFn::Blake3, content) == cid,
+            _ => false,
         }
     }
 
@@ -34,8 +275,7 @@ impl IPFSClient {
     pub fn add(&self, content: &[u8], metadata: &IPFSMetadata) -> Result<String, Box<dyn Error>> {
         // In a real implementation, this would send the content to an IPFS node
         // For demonstration, we'll just create a mock CID based on the content hash
-        let content_hash = crate::crypto::hash_sha256(&String::from_utf8_lossy(content));
-        let cid = format!("Qm{}", &content_hash[..38]);
+        let cid = self.compute_cid(content);
         
         // In a real implementation, we would also add the metadata
         let _metadata_json = serde_json::to_string(metadata)?;
@@ -43,11 +283,56 @@ impl IPFSClient {
         Ok(cid)
     }
 
+    /// Add content to IPFS from a reader, resuming from `offset`
+    ///
+    /// The reader is seeked to `offset` and read until EOF. Uploads are tracked
+    /// by `metadata.name`; once `metadata.size` bytes have arrived the upload is
+    /// finalized and the CID returned. If the reader ends early, the returned
+    /// `bytes_uploaded` is the offset to resume from.
+    ///
+    /// This is a mock implementation that buffers the partial upload in memory
+    pub fn add_resumable(
+        &self,
+        mut reader: impl Read + Seek,
+        metadata: &IPFSMetadata,
+        offset: u64,
+    ) -> Result<UploadProgress, Box<dyn Error>> {
+        let mut uploads = self.uploads.lock().map_err(|_| "Upload state poisoned")?;
+        let buffer = uploads.entry(metadata.name.clone()).or_default();
+        
+        if offset > buffer.len() as u64 {
+            return Err(format!(
+                "Resume offset ({}) is beyond the uploaded length ({})",
+                offset, buffer.len()
+            ).into());
+        }
+        buffer.truncate(offset as usize);
+        
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut chunk = vec![0u8; UPLOAD_CHUNK_SIZE];
+        loop {
+            let read = reader.read(&mut chunk)?;
+            if read == 0 {
+                break;
+            }
+            buffer.extend_from_slice(&chunk[..read]);
+        }
+        
+        let bytes_uploaded = buffer.len() as u64;
+        if buffer.len() < metadata.size {
+            return Ok(UploadProgress { bytes_uploaded, cid: None });
+        }
+        
+        let content = uploads.remove(&metadata.name).unwrap_or_default();
+        let cid = self.add(&content, metadata)?;
+        Ok(UploadProgress { bytes_uploaded, cid: Some(cid) })
+    }
+
     /// Get content from IPFS by CID
     pub fn get(&self, cid: &str) -> Result<Vec<u8>, Box<dyn Error>> {
         // In a real implementation, this would fetch the content from an IPFS node
         // For demonstration, we'll return a mock response
-        if !cid.starts_with("Qm") {
+        if !is_cid(cid) {
             return Err("Invalid CID format".into());
         }
         
@@ -56,6 +341,87 @@ impl IPFSClient {
         Ok(mock_content.into_bytes())
     }
 
+    /// Get content encrypted with `crypto::encrypt_aes_gcm` and decrypt it
+    ///
+    /// The plaintext is zeroed when the returned buffer is dropped.
+    pub fn get_and_decrypt(&self, cid: &str, key: &str) -> Result<SecureBuffer, Box<dyn Error>> {
+        let content = self.get(cid)?;
+        let encrypted = std::str::from_utf8(&content)?;
+        crypto::decrypt_aes_gcm_secure(encrypted.trim(), key)
+    }
+
+    /// Fetch content from the gateway by CID or IPNS name
+    ///
+    /// IPNS responses with an ETag are cached and revalidated on every call: the
+    /// ETag is sent as `If-None-Match` and a `304 Not Modified` response returns
+    /// the cached bytes. Immutable CIDs are not cached, so the cache holds at
+    /// most one entry per IPNS name.
+    pub async fn get_async(&self, cid_or_name: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        let immutable = is_cid(cid_or_name);
+        let path = if immutable {
+            format!("/ipfs/{}", cid_or_name)
+        } else {
+            format!("/ipns/{}", cid_or_name)
+        };
+        
+        let cached = if immutable {
+            None
+        } else {
+            self.cache.lock().map_err(|_| "Gateway cache poisoned")?.get(&path).cloned()
+        };
+        
+        self.throttle().await?;
+        let mut request = self.http.get(format!("{}{}", self.gateway_url, path));
+        if let Some(cached) = &cached {
+            request = request.header(reqwest::header::IF_NONE_MATCH, &cached.etag);
+        }
+        
+        let response = request.send().await?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                return Ok(cached.content);
+            }
+            return Err(format!("Gateway returned 304 for uncached path: {}", path).into());
+        }
+        
+        let response = response.error_for_status()?;
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+        let content = response.bytes().await?.to_vec();
+        
+        if let (false, Some(etag)) = (immutable, etag) {
+            self.cache
+                .lock()
+                .map_err(|_| "Gateway cache poisoned")?
+                .insert(path, CachedContent { etag, content: content.clone() });
+        }
+        Ok(content)
+    }
+
+    /// Fetch several CIDs from the gateway concurrently
+    ///
+    /// At most `MAX_CONCURRENT_FETCHES` requests run at once. Results are
+    /// returned in the order of `cids`, and a failure for one CID does not
+    /// affect the others.
+    pub async fn get_many(&self, cids: &[String]) -> Vec<Result<Vec<u8>, IpfsError>> {
+        stream::iter(cids)
+            .map(|cid| async move {
+                if !is_cid(cid) {
+                    return Err(IpfsError::InvalidCid(cid.clone()));
+                }
+                self.get_async(cid).await.map_err(|e| IpfsError::Fetch {
+                    cid: cid.clone(),
+                    reason: e.to_string(),
+                })
+            })
+            .buffered(MAX_CONCURRENT_FETCHES)
+            .collect()
+            .await
+    }
+
     /// Get the HTTP URL for accessing content via an IPFS gateway
     pub fn get_gateway_url(&self, cid: &str) -> String {
         format!("{}/ipfs/{}", self.gateway_url, cid)
@@ -64,24 +430,144 @@ impl IPFSClient {
     /// Pin content to ensure it remains available
     pub fn pin(&self, cid: &str) -> Result<(), Box<dyn Error>> {
         // In a real implementation, this would pin the content on an IPFS node
-        if !cid.starts_with("Qm") {
+        if !is_cid(cid) {
             return Err("Invalid CID format".into());
         }
         
-        // Just return success for the mock implementation
+        self.pins.lock().map_err(|_| "Pin set poisoned")?.insert(cid.to_string());
         Ok(())
     }
 
     /// Unpin content, allowing it to be garbage collected
     pub fn unpin(&self, cid: &str) -> Result<(), Box<dyn Error>> {
         // In a real implementation, this would unpin the content on an IPFS node
-        if !cid.starts_with("Qm") {
+        if !is_cid(cid) {
             return Err("Invalid CID format".into());
         }
         
-        // Just return success for the mock implementation
+        self.pins.lock().map_err(|_| "Pin set poisoned")?.remove(cid);
         Ok(())
     }
+
+    /// Check whether a CID is pinned
+    ///
+    /// With `with_remote_api`, this asks the node via `/pin/ls`; otherwise it
+    /// checks the pins recorded by `pin` and `unpin`.
+    pub async fn is_pinned(&self, cid: &str) -> Result<bool, Box<dyn Error>> {
+        if !is_cid(cid) {
+            return Err("Invalid CID format".into());
+        }
+        
+        if !self.use_remote_api {
+            let pins = self.pins.lock().map_err(|_| "Pin set poisoned")?;
+            return Ok(pins.contains(cid));
+        }
+        
+        self.throttle().await?;
+        let response = self
+            .http
+            .post(format!("{}/pin/ls", self.api_url))
+            .query(&[("arg", cid)])
+            .send()
+            .await?;
+        
+        if response.status().is_success() {
+            let body: serde_json::Value = response.json().await?;
+            return Ok(body["Keys"].get(cid).is_some());
+        }
+        
+        // The node answers with an error status for CIDs that are not pinned
+        let message = response.text().await?;
+        if message.contains("not pinned") {
+            Ok(false)
+        } else {
+            Err(format!("Pin status query failed: {}", message).into())
+        }
+    }
+}
+
+/// Whether a string looks like an immutable CID (CIDv0 or base32 CIDv1)
+fn is_cid(value: &str) -> bool {
+    value.starts_with("Qm") || value.starts_with("bafy") || value.starts_with("bafk")
+}
+
+/// Compute the CID of raw content under the given hash function
+fn cid_for(hash_fn: HashFn, content: &[u8]) -> String {
+    match hash_fn {
+        HashFn::Sha256 => {
+            let mut multihash = vec![MULTIHASH_SHA2_256, 32];
+            multihash.extend_from_slice(&Sha256::digest(content));
+            bs58::encode(multihash).into_string()
+        }
+        HashFn::Blake3 => {
+            let mut cid = vec![0x01, CODEC_RAW, MULTIHASH_BLAKE3, 32];
+            cid.extend_from_slice(blake3::hash(content).as_bytes());
+            format!("b{}", base32_encode(&cid))
+        }
+    }
+}
+
+/// Check that a CID is structurally valid
+///
+/// Accepts CIDv0 (base58btc SHA2-256 multihash) and base32 CIDv1 with the raw
+/// or DAG-PB codec and a 32-byte SHA2-256 or BLAKE3 digest.
+pub fn is_valid_cid(cid: &str) -> bool {
+    parse_multihash_code(cid).is_some()
+}
+
+/// Decode a CID and return its multihash code, if it is structurally valid
+fn parse_multihash_code(cid: &str) -> Option<u8> {
+    let multihash = if cid.starts_with("Qm") {
+        bs58::decode(cid).into_vec().ok()?
+    } else {
+        let bytes = base32_decode(cid.strip_prefix('b')?)?;
+        match bytes.as_slice() {
+            [0x01, CODEC_RAW | CODEC_DAG_PB, rest @ ..] => rest.to_vec(),
+            _ => return None,
+        }
+    };
+    
+    match multihash.as_slice() {
+        [code @ (MULTIHASH_SHA2_256 | MULTIHASH_BLAKE3), 32, digest @ ..] if digest.len() == 32 => Some(*code),
+        _ => None,
+    }
+}
+
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+/// Lowercase RFC 4648 base32 without padding, as used by CIDv1
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+fn base32_decode(encoded: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in encoded.bytes() {
+        let value = BASE32_ALPHABET.iter().position(|&a| a == c)? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
 }
 
 /// Create new metadata for content
@@ -104,18 +590,118 @@ pub fn create_metadata(
         encrypted,
         encryption_algorithm: encryption_algorithm.map(|s| s.to_string()),
         tags,
+        signature: None,
     }
 }
 
+/// MIME types for neural data formats that `mime_guess` does not know about
+const NEURAL_MIME_TYPES: &[(&str, &str)] = &[
+    ("edf", "application/x-edf"),
+    ("bdf", "application/x-bdf"),
+    ("vhdr", "application/x-brainvision-header"),
+    ("vmrk", "application/x-brainvision-marker"),
+    ("nii", "application/x-nifti"),
+];
+
+/// Create metadata for a file, inferring the content type from its extension
+///
+/// The name is taken from the file stem and the size from the file on disk.
+pub fn metadata_for_file(path: &Path, tags: Vec<String>) -> Result<IPFSMetadata, Box<dyn Error>> {
+    let size = std::fs::metadata(path)?.len() as usize;
+    let name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| format!("Invalid file name: {}", path.display()))?;
+    
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase());
+    let content_type = extension
+        .as_deref()
+        .and_then(|ext| NEURAL_MIME_TYPES.iter().find(|(known, _)| *known == ext))
+        .map(|(_, mime)| mime.to_string())
+        .unwrap_or_else(|| mime_guess::from_path(path).first_or_octet_stream().to_string());
+    
+    Ok(create_metadata(&content_type, name, size, false, None, tags))
+}
+
 /// Utility function to convert a CID to a gateway URL
 pub fn cid_to_url(cid: &str, gateway: &str) -> String {
     format!("{}/ipfs/{}", gateway, cid)
 }
 
+/// Check whether the content behind two CIDs differs
+///
+/// Equal CIDs address identical content, so no fetch is made in that case.
+pub fn content_changed(client: &IPFSClient, cid_a: &str, cid_b: &str) -> Result<bool, Box<dyn Error>> {
+    if cid_a == cid_b {
+        return Ok(false);
+    }
+    
+    Ok(client.get(cid_a)? != client.get(cid_b)?)
+}
+
+/// Rough magnitude of change between two versions of some content
+///
+/// Counts positions whose bytes differ, plus the difference in length.
+pub fn byte_diff_count(a: &[u8], b: &[u8]) -> usize {
+    let differing = a.iter().zip(b).filter(|(x, y)| x != y).count();
+    differing + a.len().abs_diff(b.len())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// A request received by the mock gateway
+    struct MockRequest {
+        path: String,
+        /// Header values keyed by lowercased header name
+        headers: HashMap<String, String>,
+    }
+
+    /// Serve `count` HTTP requests on a local port, answering each with the raw response from `handler`
+    ///
+    /// Returns the gateway URL and a handle that yields the requests received.
+    fn spawn_mock_gateway<F>(count: usize, handler: F) -> (String, std::thread::JoinHandle<Vec<MockRequest>>)
+    where
+        F: Fn(&MockRequest) -> String + Send + 'static,
+    {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let gateway = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for stream in listener.incoming().take(count) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut headers = HashMap::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+                    }
+                }
+
+                let path = request_line.split_whitespace().nth(1).unwrap_or("").to_string();
+                let request = MockRequest { path, headers };
+                stream.write_all(handler(&request).as_bytes()).unwrap();
+                requests.push(request);
+            }
+            requests
+        });
+        (gateway, server)
+    }
+
     #[test]
     fn test_metadata_creation() {
         let metadata = create_metadata(
@@ -135,6 +721,41 @@ mod tests {
         assert_eq!(metadata.tags.len(), 2);
     }
 
+    #[test]
+    fn test_validate_metadata_against_content() {
+        let content = b"EEG samples";
+        let consistent = create_metadata("application/octet-stream", "eeg", content.len(), false, None, Vec::new());
+        assert!(consistent.validate_against(content).is_empty());
+        
+        let drifted = create_metadata("application/octet-stream", "eeg", 4096, false, Some("AES-256-GCM"), Vec::new());
+        assert_eq!(drifted.validate_against(content), vec![
+            MetadataIssue::SizeMismatch { declared: 4096, actual: content.len() },
+            MetadataIssue::UnexpectedEncryptionAlgorithm("AES-256-GCM".to_string()),
+        ]);
+        
+        let unlabeled = create_metadata("application/octet-stream", "eeg", content.len(), true, None, Vec::new());
+        assert_eq!(unlabeled.validate_against(content), vec![MetadataIssue::MissingEncryptionAlgorithm]);
+    }
+
+    #[test]
+    fn test_sign_metadata() {
+        let (private_key, public_key) = crypto::generate_keypair();
+        let (_, other_public_key) = crypto::generate_keypair();
+        let mut metadata = create_metadata("text/plain", "notes.txt", 5, false, None, vec!["eeg".to_string()]);
+        assert!(!metadata.verify(&public_key));
+        
+        metadata.sign(&private_key).unwrap();
+        assert!(metadata.verify(&public_key));
+        assert!(!metadata.verify(&other_public_key));
+        
+        // The signature survives a JSON round trip
+        let restored: IPFSMetadata = serde_json::from_str(&serde_json::to_string(&metadata).unwrap()).unwrap();
+        assert!(restored.verify(&public_key));
+        
+        metadata.tags.push("fmri".to_string());
+        assert!(!metadata.verify(&public_key));
+    }
+
     #[test]
     fn test_ipfs_client() {
         let client = IPFSClient::new(
@@ -158,6 +779,191 @@ mod tests {
         assert!(gateway_url.contains("/ipfs/"));
     }
 
+    #[test]
+    fn test_resumable_upload() {
+        let client = IPFSClient::new(
+            "http://localhost:5001/api/v0",
+            "https://ipfs.io",
+        );
+        
+        let content: Vec<u8> = (0..1000).map(|i| b'a' + (i % 26) as u8).collect();
+        let metadata = create_metadata(
+            "application/octet-stream",
+            "fmri_run1.nii",
+            content.len(),
+            false,
+            None,
+            Vec::new(),
+        );
+        let expected_cid = client.add(&content, &metadata).unwrap();
+        
+        // The first attempt is interrupted halfway through
+        let first = client
+            .add_resumable(std::io::Cursor::new(&content[..500]), &metadata, 0)
+            .unwrap();
+        assert_eq!(first.bytes_uploaded, 500);
+        assert_eq!(first.cid, None);
+        
+        let second = client
+            .add_resumable(std::io::Cursor::new(&content), &metadata, first.bytes_uploaded)
+            .unwrap();
+        assert_eq!(second.bytes_uploaded, 1000);
+        assert_eq!(second.cid, Some(expected_cid));
+    }
+
+    #[tokio::test]
+    async fn test_get_async_uses_etag_cache() {
+        // Serves the content with an ETag, then answers the conditional request with 304
+        let (gateway, server) = spawn_mock_gateway(2, |request| {
+            if request.headers.contains_key("if-none-match") {
+                "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n".to_string()
+            } else {
+                "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: 12\r\nConnection: close\r\n\r\nmutable data".to_string()
+            }
+        });
+
+        let client = IPFSClient::new("http://localhost:5001/api/v0", &gateway);
+        let first = client.get_async("k51example").await.unwrap();
+        let second = client.get_async("k51example").await.unwrap();
+        assert_eq!(first, b"mutable data");
+        assert_eq!(second, first);
+
+        let conditional_headers: Vec<Option<String>> = server
+            .join()
+            .unwrap()
+            .into_iter()
+            .map(|request| request.headers.get("if-none-match").cloned())
+            .collect();
+        assert_eq!(conditional_headers, vec![None, Some("\"v1\"".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_spaces_requests() {
+        let (gateway, server) = spawn_mock_gateway(4, |_| {
+            "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok".to_string()
+        });
+
+        // Responses without an ETag are not cached, so every call reaches the gateway
+        let client = IPFSClient::new("http://localhost:5001/api/v0", &gateway).with_rate_limit(10.0);
+        let start = Instant::now();
+        for _ in 0..4 {
+            assert_eq!(client.get_async("k51example").await.unwrap(), b"ok");
+        }
+        // The first request is immediate; the other three wait 100 ms each
+        assert!(start.elapsed() >= Duration::from_millis(290), "took {:?}", start.elapsed());
+        server.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_many_preserves_order() {
+        let missing = "QmMissingContent";
+
+        // Echoes the requested path as the body, or 404s for the missing CID
+        let (gateway, server) = spawn_mock_gateway(4, move |request| {
+            if request.path.ends_with(missing) {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+            } else {
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    request.path.len(),
+                    request.path
+                )
+            }
+        });
+
+        let client = IPFSClient::new("http://localhost:5001/api/v0", &gateway);
+        let cids: Vec<String> = ["QmFirstFile", "not-a-cid", "QmSecondFile", missing, "bafyThirdFile"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let results = client.get_many(&cids).await;
+        server.join().unwrap();
+
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[0], Ok(b"/ipfs/QmFirstFile".to_vec()));
+        assert_eq!(results[1], Err(IpfsError::InvalidCid("not-a-cid".to_string())));
+        assert_eq!(results[2], Ok(b"/ipfs/QmSecondFile".to_vec()));
+        assert!(matches!(&results[3], Err(IpfsError::Fetch { cid, .. }) if cid == missing));
+        assert_eq!(results[4], Ok(b"/ipfs/bafyThirdFile".to_vec()));
+        
+        // Immutable content is not kept in the gateway cache
+        assert!(client.cache.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_metadata_for_file() {
+        let dir = std::env::temp_dir().join(format!("neuradesci-ipfs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        
+        let edf_path = dir.join("subject01_rest.edf");
+        std::fs::write(&edf_path, vec![0u8; 2048]).unwrap();
+        let metadata = metadata_for_file(&edf_path, vec!["EEG".to_string()]).unwrap();
+        assert_eq!(metadata.content_type, "application/x-edf");
+        assert_eq!(metadata.size, 2048);
+        assert_eq!(metadata.name, "subject01_rest");
+        assert_eq!(metadata.tags, vec!["EEG".to_string()]);
+        
+        let json_path = dir.join("events.json");
+        std::fs::write(&json_path, b"{}").unwrap();
+        let metadata = metadata_for_file(&json_path, Vec::new()).unwrap();
+        assert_eq!(metadata.content_type, "application/json");
+        
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+    
+    #[test]
+    fn test_hash_fn_cids() {
+        let content = b"EEG recording, subject S001";
+        let sha_client = IPFSClient::new("http://localhost:5001/api/v0", "https://ipfs.io");
+        let blake_client = IPFSClient::new("http://localhost:5001/api/v0", "https://ipfs.io")
+            .with_hash_fn(HashFn::Blake3);
+        
+        let sha_cid = sha_client.compute_cid(content);
+        let blake_cid = blake_client.compute_cid(content);
+        
+        assert!(sha_cid.starts_with("Qm"));
+        assert_eq!(sha_cid.len(), 46);
+        assert!(blake_cid.starts_with("bafkr4i"));
+        assert_ne!(sha_cid, blake_cid);
+        
+        assert!(is_valid_cid(&sha_cid));
+        assert!(is_valid_cid(&blake_cid));
+        assert!(!is_valid_cid("QmTest123"));
+        
+        assert!(sha_client.verify_content(&blake_cid, content));
+        assert!(blake_client.verify_content(&sha_cid, content));
+        assert!(!sha_client.verify_content(&sha_cid, b"tampered"));
+    }
+    
+    #[tokio::test]
+    async fn test_pin_status() {
+        let client = IPFSClient::new("http://localhost:5001/api/v0", "https://ipfs.io");
+        let cid = client.compute_cid(b"pinned content");
+        
+        assert!(!client.is_pinned(&cid).await.unwrap());
+        client.pin(&cid).unwrap();
+        assert!(client.is_pinned(&cid).await.unwrap());
+        client.unpin(&cid).unwrap();
+        assert!(!client.is_pinned(&cid).await.unwrap());
+    }
+    
+    #[test]
+    fn test_content_changed() {
+        let client = IPFSClient::new("http://localhost:5001/api/v0", "https://ipfs.io");
+        
+        // Fetching an invalid CID fails, so Ok(false) shows nothing was fetched
+        assert!(!content_changed(&client, "not-a-cid", "not-a-cid").unwrap());
+        assert!(content_changed(&client, "not-a-cid", "also-not-a-cid").is_err());
+        
+        let cid_a = client.compute_cid(b"version 1");
+        let cid_b = client.compute_cid(b"version 2");
+        assert!(content_changed(&client, &cid_a, &cid_b).unwrap());
+        
+        assert_eq!(byte_diff_count(b"abcdef", b"abcdef"), 0);
+        assert_eq!(byte_diff_count(b"abcdef", b"abXdef"), 1);
+        assert_eq!(byte_diff_count(b"abc", b"abXdef"), 4);
+    }
+
     #[test]
     fn test_cid_to_url() {
         let url = cid_to_url("QmTest123", "https://gateway.ipfs.io");
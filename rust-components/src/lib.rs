@@ -1,4 +1,6 @@
 use wasm_bindgen::prelude::*;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
 use serde::{Deserialize, Serialize};
 
 // Export all modules
@@ -61,6 +63,110 @@ impl ResearcherCredential {
         serde_json::from_str(json)
             .map_err(|e| JsValue::from_str(&format!("Failed to parse JSON: {}", e)))
     }
+
+    /// Issue a compact `header.payload.signature` token (base64url, JWT layout)
+    ///
+    /// The payload carries the public credential fields plus `iat` and `exp`
+    /// claims; the token expires `ttl_secs` seconds from now.
+    #[wasm_bindgen(js_name = "issueToken")]
+    pub fn issue_token(&self, private_key: &str, ttl_secs: u64) -> Result<String, JsValue> {
+        let issued_at = unix_now();
+        let claims = TokenClaims {
+            credential: self.clone(),
+            iat: issued_at,
+            exp: issued_at.saturating_add(ttl_secs),
+        };
+        let payload = serde_json::to_string(&claims)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize token: {}", e)))?;
+
+        let signing_input = format!("{}.{}", BASE64_URL.encode(TOKEN_HEADER), BASE64_URL.encode(payload));
+        let signature = crypto::sign_data(&signing_input, private_key)
+            .map_err(|e| JsValue::from_str(&format!("Failed to sign token: {}", e)))?;
+        Ok(format!("{}.{}", signing_input, BASE64_URL.encode(signature)))
+    }
+
+    /// Check a token from `issue_token` against the issuer's public key and its expiry
+    #[wasm_bindgen(js_name = "verifyToken")]
+    pub fn verify_token(token: &str, public_key: &str) -> Result<ResearcherCredential, JsValue> {
+        decode_token(token, public_key, unix_now()).map_err(|e| JsValue::from_str(&e))
+    }
+}
+
+/// Fixed header of credential tokens; the signature is `crypto::sign_data`, not a JWA algorithm
+const TOKEN_HEADER: &str = r#"{"alg":"NDS","typ":"JWT"}"#;
+
+/// Payload of a credential token
+#[derive(Serialize, Deserialize)]
+struct TokenClaims {
+    #[serde(flatten)]
+    credential: ResearcherCredential,
+    iat: u64,
+    exp: u64,
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+/// Verify a credential token as of `now`, returning the embedded credential
+fn decode_token(token: &str, public_key: &str, now: u64) -> Result<ResearcherCredential, String> {
+    let parts: Vec<&str> = token.split('.').collect();
+    let [header, payload, signature] = parts[..] else {
+        return Err("Token must have three parts".to_string());
+    };
+    let decode = |part: &str| -> Result<String, String> {
+        let bytes = BASE64_URL.decode(part).map_err(|e| format!("Invalid base64url: {}", e))?;
+        String::from_utf8(bytes).map_err(|e| format!("Invalid UTF-8: {}", e))
+    };
+
+    if decode(header)? != TOKEN_HEADER {
+        return Err("Unsupported token header".to_string());
+    }
+    let signing_input = format!("{}.{}", header, payload);
+    if !crypto::verify_signature(&signing_input, &decode(signature)?, public_key) {
+        return Err("Invalid token signature".to_string());
+    }
+
+    let claims: TokenClaims = serde_json::from_str(&decode(payload)?)
+        .map_err(|e| format!("Invalid token payload: {}", e))?;
+    if now >= claims.exp {
+        return Err("Token has expired".to_string());
+    }
+    Ok(claims.credential)
+}
+
+/// Prefix of the message signed in a key rotation attestation
+const KEY_ROTATION_DOMAIN: &str = "neuradesci-key-rotation";
+
+impl ResearcherCredential {
+    /// Replace the credential's key pair, returning a rotation attestation
+    ///
+    /// The attestation is the old key's signature over the new public key, so
+    /// anyone trusting the old public key can check it with `verify_rotation`.
+    /// If a key is already held, `old_private` must match it.
+    pub fn rotate_key(&mut self, old_private: &str, new_keypair: (String, String)) -> Result<String, JsValue> {
+        if self.private_key.as_deref().is_some_and(|current| current != old_private) {
+            return Err(JsValue::from_str("Old private key does not match the credential"));
+        }
+
+        let (new_private, new_public) = new_keypair;
+        let message = format!("{}:{}", KEY_ROTATION_DOMAIN, new_public);
+        let attestation = crypto::sign_data(&message, old_private)
+            .map_err(|e| JsValue::from_str(&format!("Failed to sign rotation: {}", e)))?;
+
+        self.private_key = Some(new_private);
+        Ok(attestation)
+    }
+}
+
+/// Check that `attestation` was produced by `old_public`'s key when rotating to `new_public`
+#[wasm_bindgen]
+pub fn verify_rotation(old_public: &str, new_public: &str, attestation: &str) -> bool {
+    let message = format!("{}:{}", KEY_ROTATION_DOMAIN, new_public);
+    crypto::verify_signature(&message, attestation, old_public)
 }
 
 /// Represents a neuroscience dataset in the NeuraDeSci ecosystem
@@ -106,18 +212,77 @@ impl NeuroscienceDataset {
         }
     }
 
+    /// Add a keyword, keeping the list normalized (see `normalize_keywords`)
     pub fn add_keyword(&mut self, keyword: &str) {
         self.keywords.push(keyword.to_string());
+        self.normalize_keywords();
+    }
+
+    /// Lowercase and trim keywords, dropping empty ones and duplicates
+    ///
+    /// The first occurrence of each keyword keeps its position.
+    #[wasm_bindgen(js_name = "normalizeKeywords")]
+    pub fn normalize_keywords(&mut self) {
+        let mut normalized: Vec<String> = Vec::with_capacity(self.keywords.len());
+        for keyword in &self.keywords {
+            let keyword = keyword.trim().to_lowercase();
+            if !keyword.is_empty() && !normalized.contains(&keyword) {
+                normalized.push(keyword);
+            }
+        }
+        self.keywords = normalized;
     }
 
     pub fn set_private(&mut self, is_private: bool) {
         self.is_private = is_private;
     }
 
+    /// Stable SHA-256 fingerprint for deduplication, independent of the CID scheme
+    ///
+    /// Combines the trimmed, case-folded title, data type and owner with the
+    /// trimmed IPFS hash (which is case-sensitive and kept as-is).
+    pub fn content_fingerprint(&self) -> String {
+        let normalized = serde_json::json!([
+            self.title.trim().to_lowercase(),
+            self.data_type.trim().to_lowercase(),
+            self.owner_id.trim().to_lowercase(),
+            self.ipfs_hash.trim(),
+        ]);
+        crypto::hash_sha256(&crypto::canonical_json_value(&normalized))
+    }
+
     pub fn to_json(&self) -> String {
         serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
     }
 
+    /// JSON containing only the fields that are safe to publish in a catalog
+    ///
+    /// The owner is never included. Private datasets are reduced to a stub
+    /// without the description, keywords or IPFS hash.
+    #[wasm_bindgen(js_name = "toPublicJson")]
+    pub fn to_public_json(&self) -> String {
+        let public = if self.is_private {
+            serde_json::json!({
+                "title": self.title,
+                "data_type": self.data_type,
+                "license": self.license,
+                "timestamp": self.timestamp,
+                "is_private": true,
+            })
+        } else {
+            serde_json::json!({
+                "title": self.title,
+                "description": self.description,
+                "data_type": self.data_type,
+                "ipfs_hash": self.ipfs_hash,
+                "license": self.license,
+                "keywords": self.keywords,
+                "timestamp": self.timestamp,
+            })
+        };
+        public.to_string()
+    }
+
     #[wasm_bindgen(js_name = "fromJson")]
     pub fn from_json(json: &str) -> Result<NeuroscienceDataset, JsValue> {
         serde_json::from_str(json)
@@ -125,6 +290,284 @@ impl NeuroscienceDataset {
     }
 }
 
+/// A dataset license, as far as reuse permissions are concerned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum License {
+    CcBy,
+    Cc0,
+    /// Any CC BY-NC variant, including BY-NC-SA and BY-NC-ND
+    CcByNc,
+    Proprietary,
+    /// A license string that is not recognized; its terms are unknown
+    Custom(String),
+}
+
+impl License {
+    /// Parse a license string such as `"CC-BY-4.0"`, `"cc by-nc"` or `"CC0 1.0"`
+    ///
+    /// Matching ignores case, version numbers and whether words are separated
+    /// by spaces, hyphens or underscores. Unrecognized strings become `Custom`.
+    pub fn parse(license: &str) -> License {
+        let normalized = license.trim().to_uppercase().replace([' ', '_'], "-");
+        let name = match normalized.rsplit_once('-') {
+            Some((name, version)) if version.chars().all(|c| c.is_ascii_digit() || c == '.') => name,
+            _ => normalized.as_str(),
+        };
+
+        match name {
+            "CC0" => License::Cc0,
+            "CC-BY" => License::CcBy,
+            "PROPRIETARY" | "ALL-RIGHTS-RESERVED" => License::Proprietary,
+            _ if name.starts_with("CC-BY-NC") => License::CcByNc,
+            _ => License::Custom(license.trim().to_string()),
+        }
+    }
+
+    /// Whether the license allows the given use
+    ///
+    /// Proprietary and unrecognized licenses permit nothing without a separate
+    /// agreement with the owner.
+    pub fn permits(&self, intent: UsageIntent) -> bool {
+        match self {
+            License::Cc0 | License::CcBy => true,
+            License::CcByNc => intent != UsageIntent::Commercial,
+            License::Proprietary | License::Custom(_) => false,
+        }
+    }
+}
+
+/// How a dataset is going to be used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageIntent {
+    Commercial,
+    Research,
+    Redistribution,
+}
+
+/// A single field that differs between two versions of a dataset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+impl NeuroscienceDataset {
+    /// The dataset's license, parsed from its license string
+    pub fn license(&self) -> License {
+        License::parse(&self.license)
+    }
+
+    /// Whether the dataset's license allows the given use
+    pub fn is_usage_permitted(&self, intent: UsageIntent) -> bool {
+        self.license().permits(intent)
+    }
+
+    /// List the fields that differ between this dataset and `other`
+    ///
+    /// Keywords are compared as a set: each added keyword is reported with an
+    /// empty `old` value and each removed keyword with an empty `new` value.
+    pub fn diff(&self, other: &NeuroscienceDataset) -> Vec<FieldChange> {
+        let mut changes = Vec::new();
+        let mut compare = |field: &str, old: String, new: String| {
+            if old != new {
+                changes.push(FieldChange { field: field.to_string(), old, new });
+            }
+        };
+
+        compare("id", self.id.clone(), other.id.clone());
+        compare("title", self.title.clone(), other.title.clone());
+        compare("description", self.description.clone(), other.description.clone());
+        compare("data_type", self.data_type.clone(), other.data_type.clone());
+        compare("ipfs_hash", self.ipfs_hash.clone(), other.ipfs_hash.clone());
+        compare("owner_id", self.owner_id.clone(), other.owner_id.clone());
+        compare("timestamp", self.timestamp.to_string(), other.timestamp.to_string());
+        compare("license", self.license.clone(), other.license.clone());
+        compare("is_private", self.is_private.to_string(), other.is_private.to_string());
+
+        for keyword in &self.keywords {
+            if !other.keywords.contains(keyword) {
+                compare("keywords", keyword.clone(), String::new());
+            }
+        }
+        for keyword in &other.keywords {
+            if !self.keywords.contains(keyword) {
+                compare("keywords", String::new(), keyword.clone());
+            }
+        }
+
+        changes
+    }
+}
+
+/// Outcome of `DatasetRegistry::import_dir`, per file
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Files whose dataset was added to the registry
+    pub imported: Vec<std::path::PathBuf>,
+    /// Files that could not be read or parsed, with the reason; unreadable
+    /// directory entries are reported under the directory's path
+    pub failed: Vec<(std::path::PathBuf, String)>,
+}
+
+/// A local collection of datasets
+#[derive(Default, Serialize, Deserialize)]
+pub struct DatasetRegistry {
+    pub datasets: Vec<NeuroscienceDataset>,
+}
+
+impl DatasetRegistry {
+    pub fn new() -> Self {
+        DatasetRegistry::default()
+    }
+
+    pub fn add(&mut self, dataset: NeuroscienceDataset) {
+        self.datasets.push(dataset);
+    }
+
+    /// Add every dataset stored as a `.json` file directly inside `path`
+    ///
+    /// Files are processed in name order. A directory entry or file that
+    /// cannot be read or parsed is recorded in the report and the import
+    /// continues; only failing to list the directory itself is an error.
+    pub fn import_dir(&mut self, path: &std::path::Path) -> Result<ImportReport, Box<dyn std::error::Error>> {
+        let mut report = ImportReport::default();
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(path)? {
+            let file = match entry {
+                Ok(entry) => entry.path(),
+                Err(e) => {
+                    // The entry's name is unknown, so the directory stands in for it
+                    report.failed.push((path.to_path_buf(), e.to_string()));
+                    continue;
+                }
+            };
+            let is_json = file.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+            if is_json && file.is_file() {
+                files.push(file);
+            }
+        }
+        files.sort();
+
+        for file in files {
+            let parsed = std::fs::read_to_string(&file)
+                .map_err(|e| e.to_string())
+                .and_then(|json| serde_json::from_str::<NeuroscienceDataset>(&json).map_err(|e| e.to_string()));
+            match parsed {
+                Ok(dataset) => {
+                    self.add(dataset);
+                    report.imported.push(file);
+                }
+                Err(reason) => report.failed.push((file, reason)),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Group datasets sharing a content fingerprint
+    ///
+    /// Only groups with more than one dataset are returned, in order of first appearance.
+    pub fn find_duplicates(&self) -> Vec<Vec<&NeuroscienceDataset>> {
+        let mut groups: Vec<(String, Vec<&NeuroscienceDataset>)> = Vec::new();
+        for dataset in &self.datasets {
+            let fingerprint = dataset.content_fingerprint();
+            match groups.iter_mut().find(|(existing, _)| *existing == fingerprint) {
+                Some((_, group)) => group.push(dataset),
+                None => groups.push((fingerprint, vec![dataset])),
+            }
+        }
+
+        groups
+            .into_iter()
+            .map(|(_, group)| group)
+            .filter(|group| group.len() > 1)
+            .collect()
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl DatasetRegistry {
+    /// Write the registry to a Parquet file, one row per dataset
+    ///
+    /// Columns: id, title, data_type, owner_id, timestamp, license,
+    /// is_private and keyword_count.
+    pub fn to_parquet(&self, path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+        use std::sync::Arc;
+        use arrow_array::{ArrayRef, BooleanArray, RecordBatch, StringArray, UInt32Array, UInt64Array};
+        use arrow_schema::{DataType, Field, Schema};
+        use parquet::arrow::ArrowWriter;
+
+        let strings = |field: fn(&NeuroscienceDataset) -> &str| -> ArrayRef {
+            Arc::new(StringArray::from_iter_values(self.datasets.iter().map(field)))
+        };
+        let columns: Vec<ArrayRef> = vec![
+            strings(|d| &d.id),
+            strings(|d| &d.title),
+            strings(|d| &d.data_type),
+            strings(|d| &d.owner_id),
+            Arc::new(UInt64Array::from_iter_values(self.datasets.iter().map(|d| d.timestamp))),
+            strings(|d| &d.license),
+            Arc::new(BooleanArray::from_iter(self.datasets.iter().map(|d| Some(d.is_private)))),
+            Arc::new(UInt32Array::from_iter_values(self.datasets.iter().map(|d| d.keywords.len() as u32))),
+        ];
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new("title", DataType::Utf8, false),
+            Field::new("data_type", DataType::Utf8, false),
+            Field::new("owner_id", DataType::Utf8, false),
+            Field::new("timestamp", DataType::UInt64, false),
+            Field::new("license", DataType::Utf8, false),
+            Field::new("is_private", DataType::Boolean, false),
+            Field::new("keyword_count", DataType::UInt32, false),
+        ]));
+
+        let batch = RecordBatch::try_new(schema.clone(), columns)?;
+        let file = std::fs::File::create(path)?;
+        let mut writer = ArrowWriter::try_new(file, schema, None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+}
+
+/// Outcome of each check performed by `verify_dataset_bundle`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationReport {
+    /// The dataset's `ipfs_hash` is a structurally valid CID
+    pub cid_valid: bool,
+    /// The content hashes to the dataset's `ipfs_hash`
+    pub content_valid: bool,
+    /// The owner's signature over the dataset's canonical JSON verifies
+    pub signature_valid: bool,
+}
+
+impl VerificationReport {
+    /// Whether every check passed
+    pub fn is_valid(&self) -> bool {
+        self.cid_valid && self.content_valid && self.signature_valid
+    }
+}
+
+/// Verify a shared dataset bundle: its metadata, content and owner signature
+///
+/// The signature is expected over the dataset's canonical JSON, as produced by
+/// `crypto::sign_json`. Every check is run and reported, even if an earlier one fails.
+pub fn verify_dataset_bundle(
+    dataset: &NeuroscienceDataset,
+    content: &[u8],
+    signature: &str,
+    owner_public_key: &str,
+    client: &ipfs::IPFSClient,
+) -> Result<VerificationReport, Box<dyn std::error::Error>> {
+    let message = crypto::canonical_json(dataset)?;
+
+    Ok(VerificationReport {
+        cid_valid: ipfs::is_valid_cid(&dataset.ipfs_hash),
+        content_valid: client.verify_content(&dataset.ipfs_hash, content),
+        signature_valid: crypto::verify_signature(&message, signature, owner_public_key),
+    })
+}
+
 // Re-export key functions directly at the root level for easier access
 // These are convenience wrappers around the module functions
 
@@ -158,4 +601,275 @@ pub fn version() -> String {
     let version = env!("CARGO_PKG_VERSION");
     let name = env!("CARGO_PKG_NAME");
     format!("{} v{}", name, version)
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_dataset() -> NeuroscienceDataset {
+        let mut dataset = NeuroscienceDataset::new(
+            "ds-001",
+            "Resting-state EEG",
+            "Eyes-closed resting-state recordings",
+            "EEG",
+            "QmSampleHash",
+            "researcher_001",
+            1700000000,
+            "CC-BY-4.0",
+        );
+        dataset.add_keyword("EEG");
+        dataset
+    }
+
+    #[test]
+    fn test_verify_dataset_bundle() {
+        let client = ipfs::IPFSClient::new("http://localhost:5001/api/v0", "https://ipfs.io");
+        let content = b"{\"channels\": [\"Fz\", \"Cz\"]}";
+        let (private_key, public_key) = crypto::generate_keypair();
+
+        let mut dataset = sample_dataset();
+        dataset.ipfs_hash = client.compute_cid(content);
+        let signature = crypto::sign_json(&dataset, &private_key).unwrap();
+
+        let report = verify_dataset_bundle(&dataset, content, &signature, &public_key, &client).unwrap();
+        assert!(report.is_valid());
+
+        // Content that does not match the CID
+        let report = verify_dataset_bundle(&dataset, b"tampered", &signature, &public_key, &client).unwrap();
+        assert_eq!(report, VerificationReport { cid_valid: true, content_valid: false, signature_valid: true });
+
+        // Signature checked against someone else's key
+        let (_, other_public_key) = crypto::generate_keypair();
+        let report = verify_dataset_bundle(&dataset, content, &signature, &other_public_key, &client).unwrap();
+        assert_eq!(report, VerificationReport { cid_valid: true, content_valid: true, signature_valid: false });
+
+        // Malformed CID in the metadata, re-signed by the owner
+        dataset.ipfs_hash = "QmSampleHash".to_string();
+        let signature = crypto::sign_json(&dataset, &private_key).unwrap();
+        let report = verify_dataset_bundle(&dataset, content, &signature, &public_key, &client).unwrap();
+        assert_eq!(report, VerificationReport { cid_valid: false, content_valid: false, signature_valid: true });
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_to_parquet() {
+        use arrow_array::{Array, StringArray, UInt32Array};
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let mut registry = DatasetRegistry::new();
+        for id in ["ds-001", "ds-002", "ds-003"] {
+            let mut dataset = sample_dataset();
+            dataset.id = id.to_string();
+            registry.add(dataset);
+        }
+        registry.datasets[1].add_keyword("resting-state");
+
+        let path = std::env::temp_dir().join(format!("neuradesci-registry-{}.parquet", std::process::id()));
+        registry.to_parquet(&path).unwrap();
+
+        let file = std::fs::File::open(&path).unwrap();
+        let batches: Vec<_> = ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(batches.iter().map(|batch| batch.num_rows()).sum::<usize>(), 3);
+        let batch = &batches[0];
+        let ids = batch.column_by_name("id").unwrap().as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(ids.value(1), "ds-002");
+        let keyword_counts = batch.column_by_name("keyword_count").unwrap().as_any().downcast_ref::<UInt32Array>().unwrap();
+        assert_eq!(keyword_counts.value(1), 2);
+        assert_eq!(keyword_counts.len(), 3);
+    }
+
+    #[test]
+    fn test_normalize_keywords() {
+        let mut dataset = sample_dataset();
+        dataset.add_keyword("fMRI");
+        dataset.add_keyword("FMRI");
+        dataset.add_keyword(" fmri ");
+        dataset.add_keyword("  ");
+        assert_eq!(dataset.keywords, vec!["eeg".to_string(), "fmri".to_string()]);
+
+        // Keywords set directly, e.g. from JSON, are normalized on request
+        dataset.keywords = vec!["Motor".to_string(), "EEG".to_string(), "motor ".to_string()];
+        dataset.normalize_keywords();
+        assert_eq!(dataset.keywords, vec!["motor".to_string(), "eeg".to_string()]);
+    }
+
+    #[test]
+    fn test_license_usage() {
+        assert_eq!(License::parse("CC-BY-4.0"), License::CcBy);
+        assert_eq!(License::parse("cc by"), License::CcBy);
+        assert_eq!(License::parse("CC0 1.0"), License::Cc0);
+        assert_eq!(License::parse("CC0"), License::Cc0);
+        assert_eq!(License::parse("CC-BY-NC-SA-4.0"), License::CcByNc);
+        assert_eq!(License::parse("Proprietary"), License::Proprietary);
+        assert_eq!(License::parse(" ODbL "), License::Custom("ODbL".to_string()));
+
+        let mut dataset = sample_dataset();
+        assert!(dataset.is_usage_permitted(UsageIntent::Commercial));
+
+        dataset.license = "CC-BY-NC-4.0".to_string();
+        assert!(dataset.is_usage_permitted(UsageIntent::Research));
+        assert!(dataset.is_usage_permitted(UsageIntent::Redistribution));
+        assert!(!dataset.is_usage_permitted(UsageIntent::Commercial));
+
+        dataset.license = "ODbL".to_string();
+        assert!(!dataset.is_usage_permitted(UsageIntent::Research));
+    }
+
+    #[test]
+    fn test_import_dir() {
+        let dir = std::env::temp_dir().join(format!("neuradesci-import-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut second = sample_dataset();
+        second.id = "ds-002".to_string();
+        std::fs::write(dir.join("a.json"), sample_dataset().to_json()).unwrap();
+        std::fs::write(dir.join("b.json"), second.to_json()).unwrap();
+        std::fs::write(dir.join("c.json"), "{\"id\": \"ds-003\"").unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a dataset").unwrap();
+
+        let mut registry = DatasetRegistry::new();
+        let report = registry.import_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(report.imported, vec![dir.join("a.json"), dir.join("b.json")]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, dir.join("c.json"));
+        let ids: Vec<&str> = registry.datasets.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, ["ds-001", "ds-002"]);
+
+        assert!(registry.import_dir(&dir).is_err());
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let mut registry = DatasetRegistry::new();
+
+        let original = sample_dataset();
+        let mut reupload = sample_dataset();
+        reupload.id = "ds-002".to_string();
+        reupload.title = "  resting-state eeg ".to_string();
+        reupload.description = "Same recording, re-registered".to_string();
+        let mut unrelated = sample_dataset();
+        unrelated.id = "ds-003".to_string();
+        unrelated.ipfs_hash = "QmOtherHash".to_string();
+
+        assert_eq!(original.content_fingerprint(), reupload.content_fingerprint());
+        assert_ne!(original.content_fingerprint(), unrelated.content_fingerprint());
+
+        registry.add(original);
+        registry.add(unrelated);
+        registry.add(reupload);
+
+        let duplicates = registry.find_duplicates();
+        assert_eq!(duplicates.len(), 1);
+        let ids: Vec<&str> = duplicates[0].iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, vec!["ds-001", "ds-002"]);
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let mut credential = ResearcherCredential::new("r-001", "Ada", "Neurophysiology", "Institute");
+        let (old_private, old_public) = crypto::generate_keypair();
+        let (new_private, new_public) = crypto::generate_keypair();
+
+        let attestation = credential
+            .rotate_key(&old_private, (new_private.clone(), new_public.clone()))
+            .unwrap();
+        assert_eq!(credential.private_key.as_deref(), Some(new_private.as_str()));
+        assert!(verify_rotation(&old_public, &new_public, &attestation));
+
+        // Attestation checked against a key that did not sign it
+        let (_, wrong_public) = crypto::generate_keypair();
+        assert!(!verify_rotation(&wrong_public, &new_public, &attestation));
+
+        // Attestation replayed for a different new key
+        let (_, other_public) = crypto::generate_keypair();
+        assert!(!verify_rotation(&old_public, &other_public, &attestation));
+
+        // Signed by a key other than the old one
+        let (forger_private, _) = crypto::generate_keypair();
+        let forged = crypto::sign_data(&format!("{}:{}", KEY_ROTATION_DOMAIN, new_public), &forger_private).unwrap();
+        assert!(!verify_rotation(&old_public, &new_public, &forged));
+    }
+
+    #[test]
+    fn test_credential_token() {
+        let mut credential = ResearcherCredential::new("r-001", "Ada", "Neurophysiology", "Institute");
+        credential.add_publication("doi:10.1000/182");
+        let (private_key, public_key) = crypto::generate_keypair();
+
+        let token = credential.issue_token(&private_key, 3600).unwrap();
+        assert_eq!(token.split('.').count(), 3);
+        let verified = ResearcherCredential::verify_token(&token, &public_key).unwrap();
+        assert_eq!(verified.to_json(), credential.to_json());
+
+        // Expired: checked one hour and one second later
+        assert_eq!(decode_token(&token, &public_key, unix_now() + 3601).err().as_deref(), Some("Token has expired"));
+
+        // Payload swapped for another researcher's, keeping the signature
+        let parts: Vec<&str> = token.split('.').collect();
+        let forged_claims = TokenClaims {
+            credential: ResearcherCredential::new("r-999", "Mallory", "Neurophysiology", "Institute"),
+            iat: unix_now(),
+            exp: unix_now() + 3600,
+        };
+        let forged_payload = BASE64_URL.encode(serde_json::to_string(&forged_claims).unwrap());
+        let tampered = format!("{}.{}.{}", parts[0], forged_payload, parts[2]);
+        assert_eq!(decode_token(&tampered, &public_key, unix_now()).err().as_deref(), Some("Invalid token signature"));
+
+        // Signed by a different key
+        let (_, other_public) = crypto::generate_keypair();
+        assert!(decode_token(&token, &other_public, unix_now()).is_err());
+        assert!(decode_token("not-a-token", &public_key, unix_now()).is_err());
+    }
+
+    #[test]
+    fn test_public_json() {
+        let mut dataset = sample_dataset();
+        let public: serde_json::Value = serde_json::from_str(&dataset.to_public_json()).unwrap();
+        assert_eq!(public["ipfs_hash"], "QmSampleHash");
+        assert_eq!(public["keywords"], serde_json::json!(["eeg"]));
+        assert!(public.get("owner_id").is_none());
+        assert!(public.get("id").is_none());
+
+        dataset.set_private(true);
+        let stub: serde_json::Value = serde_json::from_str(&dataset.to_public_json()).unwrap();
+        assert!(stub.get("ipfs_hash").is_none());
+        assert!(stub.get("description").is_none());
+        assert!(stub.get("owner_id").is_none());
+        assert_eq!(stub["title"], "Resting-state EEG");
+        assert_eq!(stub["is_private"], true);
+    }
+
+    #[test]
+    fn test_dataset_diff() {
+        let original = sample_dataset();
+        let mut revised = sample_dataset();
+        revised.title = "Resting-state EEG (v2)".to_string();
+        revised.add_keyword("sleep");
+
+        let changes = original.diff(&revised);
+        assert_eq!(changes, vec![
+            FieldChange {
+                field: "title".to_string(),
+                old: "Resting-state EEG".to_string(),
+                new: "Resting-state EEG (v2)".to_string(),
+            },
+            FieldChange {
+                field: "keywords".to_string(),
+                old: String::new(),
+                new: "sleep".to_string(),
+            },
+        ]);
+
+        assert!(original.diff(&sample_dataset()).is_empty());
+    }
+}
@@ -1,6 +1,21 @@
 use serde::{Serialize, Deserialize};
-use std::collections::HashMap;
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, VecDeque};
 use std::error::Error;
+use std::f64::consts::FRAC_1_SQRT_2;
+use std::io::{Read, Write};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use nalgebra::DMatrix;
+use rustfft::FftPlanner;
+use rustfft::num_complex::Complex;
+
+use crate::blockchain::{Transaction, TransactionType};
+use crate::crypto;
 
 /// Represents the format of neural data
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
@@ -17,6 +32,120 @@ pub enum NeuralDataFormat {
     Custom,
 }
 
+impl NeuralDataFormat {
+    /// BIDS identifier for this format
+    ///
+    /// Uses the BIDS datatype for electrophysiology (`eeg`, `meg`, `ieeg`) and
+    /// the file suffix for imaging (`bold`, `T1w`, `pet`). CT, SPECT and
+    /// single-unit recordings have no official BIDS modality, so lowercase
+    /// names following the same pattern are used.
+    pub fn as_bids_modality(&self) -> &'static str {
+        match self {
+            NeuralDataFormat::EEG => "eeg",
+            NeuralDataFormat::FMRI => "bold",
+            NeuralDataFormat::MEG => "meg",
+            NeuralDataFormat::PET => "pet",
+            NeuralDataFormat::MRI => "T1w",
+            NeuralDataFormat::CT => "ct",
+            NeuralDataFormat::SPECT => "spect",
+            NeuralDataFormat::ECOG => "ieeg",
+            NeuralDataFormat::SingleUnitRecording => "ecephys",
+            NeuralDataFormat::Custom => "custom",
+        }
+    }
+    
+    /// Parse a BIDS identifier produced by `as_bids_modality` (case-insensitive)
+    pub fn from_bids_modality(s: &str) -> Option<NeuralDataFormat> {
+        let format = match s.to_ascii_lowercase().as_str() {
+            "eeg" => NeuralDataFormat::EEG,
+            "bold" => NeuralDataFormat::FMRI,
+            "meg" => NeuralDataFormat::MEG,
+            "pet" => NeuralDataFormat::PET,
+            "t1w" => NeuralDataFormat::MRI,
+            "ct" => NeuralDataFormat::CT,
+            "spect" => NeuralDataFormat::SPECT,
+            "ieeg" => NeuralDataFormat::ECOG,
+            "ecephys" => NeuralDataFormat::SingleUnitRecording,
+            "custom" => NeuralDataFormat::Custom,
+            _ => return None,
+        };
+        Some(format)
+    }
+    
+    /// MIME type of the file format this kind of data is usually stored in
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            NeuralDataFormat::EEG | NeuralDataFormat::ECOG => "application/x-edf",
+            NeuralDataFormat::MEG => "application/x-fif",
+            NeuralDataFormat::FMRI | NeuralDataFormat::MRI | NeuralDataFormat::PET => "application/x-nifti",
+            NeuralDataFormat::CT | NeuralDataFormat::SPECT => "application/dicom",
+            NeuralDataFormat::SingleUnitRecording => "application/x-nwb",
+            NeuralDataFormat::Custom => "application/octet-stream",
+        }
+    }
+    
+    /// MNE-Python channel type for channels of this format
+    ///
+    /// MEG channels are reported as magnetometers; formats without an MNE
+    /// equivalent map to `misc`.
+    pub fn mne_channel_type(&self) -> &'static str {
+        match self {
+            NeuralDataFormat::EEG => "eeg",
+            NeuralDataFormat::MEG => "mag",
+            NeuralDataFormat::ECOG => "ecog",
+            _ => "misc",
+        }
+    }
+}
+
+/// Typical shape of a recording for a given format, used by `validate_for_format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatExpectations {
+    /// Fewer channels than this triggers a warning
+    pub min_channels: usize,
+}
+
+impl FormatExpectations {
+    /// Default expectations for `format`
+    ///
+    /// For fMRI the minimum is the number of voxels (channels) below which
+    /// the series is treated as a single flat time course.
+    pub fn for_format(format: NeuralDataFormat) -> Self {
+        let min_channels = match format {
+            NeuralDataFormat::EEG | NeuralDataFormat::ECOG => 8,
+            NeuralDataFormat::MEG => 32,
+            NeuralDataFormat::FMRI => 2,
+            _ => 1,
+        };
+        FormatExpectations { min_channels }
+    }
+}
+
+/// Something unusual about a series for its declared format; not an error
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ValidationWarning {
+    /// Fewer channels than the format usually has
+    LowChannelCount {
+        format: NeuralDataFormat,
+        channels: usize,
+        minimum: usize,
+    },
+    /// An fMRI series stored as a single time course rather than per voxel
+    FlatFmriSeries,
+}
+
+/// Number of values per array measured by `estimated_size_bytes`
+const SIZE_ESTIMATE_SAMPLES: usize = 64;
+
+/// Approximate JSON size of the leading segment compressed by `estimated_compressed_size_bytes`
+const COMPRESSION_PROBE_BYTES: usize = 64 * 1024;
+
+/// Theta band in Hz, as used by `theta_beta_ratio`
+pub const THETA_BAND: (f64, f64) = (4.0, 8.0);
+
+/// Beta band in Hz, as used by `theta_beta_ratio`
+pub const BETA_BAND: (f64, f64) = (13.0, 30.0);
+
 /// Represents a time series of neural data
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NeuralTimeSeries {
@@ -24,9 +153,14 @@ pub struct NeuralTimeSeries {
     pub sampling_rate: f64, // Hz
     pub channels: Vec<String>,
     pub timestamps: Vec<f64>,
-    pub data: Vec<Vec<f64>>, // channel x time
+    /// Channel x time. NaN samples (e.g. from `reject_outliers_iqr`) serialize as `null`
+    #[serde(deserialize_with = "deserialize_samples")]
+    pub data: Vec<Vec<f64>>,
     pub units: String,
     pub metadata: HashMap<String, String>,
+    /// Electrode coordinates by channel name, for topographic plots and source localization
+    #[serde(default)]
+    pub channel_positions: HashMap<String, (f64, f64, f64)>,
 }
 
 impl NeuralTimeSeries {
@@ -40,6 +174,7 @@ impl NeuralTimeSeries {
             data: Vec::new(),
             units: units.to_string(),
             metadata: HashMap::new(),
+            channel_positions: HashMap::new(),
         }
     }
     
@@ -75,17 +210,436 @@ impl NeuralTimeSeries {
             .collect();
     }
     
+    /// Number of samples per channel
+    pub fn sample_count(&self) -> usize {
+        if !self.timestamps.is_empty() {
+            return self.timestamps.len();
+        }
+        self.data.first().map_or(0, |channel| channel.len())
+    }
+    
+    /// Recording length in seconds
+    ///
+    /// Uses the span of `timestamps` when present, otherwise the sample count
+    /// divided by the sampling rate.
+    pub fn duration_seconds(&self) -> f64 {
+        if let (Some(first), Some(last)) = (self.timestamps.first(), self.timestamps.last()) {
+            return last - first;
+        }
+        if self.sampling_rate <= 0.0 {
+            return 0.0;
+        }
+        self.sample_count() as f64 / self.sampling_rate
+    }
+    
+    /// Split into consecutive, non-overlapping segments of `segment_samples`
+    ///
+    /// A trailing partial segment is dropped. Each segment's timestamps are
+    /// re-based to start at 0 and the metadata is cloned.
+    pub fn split_fixed(&self, segment_samples: usize) -> Vec<NeuralTimeSeries> {
+        self.split_segments(segment_samples, None)
+    }
+    
+    /// Like `split_fixed`, but pads a trailing partial segment with `pad_value`
+    pub fn split_fixed_padded(&self, segment_samples: usize, pad_value: f64) -> Vec<NeuralTimeSeries> {
+        self.split_segments(segment_samples, Some(pad_value))
+    }
+    
+    fn split_segments(&self, segment_samples: usize, pad_value: Option<f64>) -> Vec<NeuralTimeSeries> {
+        let total = self.sample_count();
+        if segment_samples == 0 || total == 0 {
+            return Vec::new();
+        }
+        
+        let num_segments = match pad_value {
+            Some(_) => total.div_ceil(segment_samples),
+            None => total / segment_samples,
+        };
+        let dt = if self.sampling_rate > 0.0 { 1.0 / self.sampling_rate } else { 0.0 };
+        let timestamps = self.effective_timestamps();
+        
+        (0..num_segments)
+            .map(|s| {
+                let start = s * segment_samples;
+                let mut segment = NeuralTimeSeries::new(self.format, self.sampling_rate, &self.units);
+                segment.metadata = self.metadata.clone();
+                segment.channel_positions = self.channel_positions.clone();
+                segment.channels = self.channels.clone();
+                segment.data = self.data
+                    .iter()
+                    .map(|channel| {
+                        (start..start + segment_samples)
+                            .map(|i| channel.get(i).copied().or(pad_value).unwrap_or(0.0))
+                            .collect()
+                    })
+                    .collect();
+                
+                if let Some(&base) = timestamps.get(start) {
+                    segment.timestamps = (start..start + segment_samples)
+                        .map(|i| match timestamps.get(i) {
+                            Some(&t) => t - base,
+                            None => (i - start) as f64 * dt,
+                        })
+                        .collect();
+                }
+                segment
+            })
+            .collect()
+    }
+    
+    /// Crop flat or zero padding from both ends of the recording
+    ///
+    /// Keeps samples from the first to the last index where any channel's
+    /// absolute value exceeds `threshold`, and returns that retained range as
+    /// `(start, end)` in the original indexing (end exclusive). If no sample
+    /// exceeds the threshold, all samples are removed and `(0, 0)` is returned.
+    pub fn trim(&mut self, threshold: f64) -> (usize, usize) {
+        let num_samples = self.data.iter().map(|channel| channel.len()).max().unwrap_or(0);
+        let is_active = |i: usize| {
+            self.data
+                .iter()
+                .any(|channel| channel.get(i).is_some_and(|v| v.abs() > threshold))
+        };
+        
+        let (start, end) = match (0..num_samples).find(|&i| is_active(i)) {
+            Some(start) => {
+                let last = (start..num_samples).rev().find(|&i| is_active(i)).unwrap_or(start);
+                (start, last + 1)
+            }
+            None => (0, 0),
+        };
+        
+        for channel in &mut self.data {
+            let channel_end = end.min(channel.len());
+            let channel_start = start.min(channel_end);
+            *channel = channel[channel_start..channel_end].to_vec();
+        }
+        if !self.timestamps.is_empty() {
+            let ts_end = end.min(self.timestamps.len());
+            self.timestamps = self.timestamps[start.min(ts_end)..ts_end].to_vec();
+        }
+        
+        (start, end)
+    }
+    
+    /// Copy of the series containing only the named channels, in the given order
+    ///
+    /// Timestamps, metadata and the selected channels' positions are kept.
+    pub fn select_channels(&self, names: &[String]) -> Result<NeuralTimeSeries, Box<dyn Error>> {
+        let indices = names
+            .iter()
+            .map(|name| {
+                self.channels
+                    .iter()
+                    .position(|channel| channel == name)
+                    .ok_or_else(|| format!("Channel not found: {}", name))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self.with_channels(&indices))
+    }
+    
+    /// Copy of the series containing only the channels whose names match `predicate`
+    pub fn select_channels_matching(&self, predicate: impl Fn(&str) -> bool) -> NeuralTimeSeries {
+        let indices: Vec<usize> = self
+            .channels
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| predicate(name))
+            .map(|(i, _)| i)
+            .collect();
+        self.with_channels(&indices)
+    }
+    
+    fn with_channels(&self, indices: &[usize]) -> NeuralTimeSeries {
+        let mut selected = NeuralTimeSeries::new(self.format, self.sampling_rate, &self.units);
+        selected.timestamps = self.timestamps.clone();
+        selected.metadata = self.metadata.clone();
+        for &i in indices {
+            let name = &self.channels[i];
+            selected.channels.push(name.clone());
+            selected.data.push(self.data[i].clone());
+            if let Some(&position) = self.channel_positions.get(name) {
+                selected.channel_positions.insert(name.clone(), position);
+            }
+        }
+        selected
+    }
+    
+    /// Root-mean-square amplitude envelope over a centered sliding window
+    ///
+    /// Sample `i` uses samples `i - (window - 1) / 2 ..= i + window / 2`; near the
+    /// edges the window shrinks to the samples available.
+    pub fn moving_rms(&self, channel: &str, window: usize) -> Result<Vec<f64>, Box<dyn Error>> {
+        let data = self
+            .get_channel_data(channel)
+            .ok_or_else(|| format!("Channel not found: {}", channel))?;
+        if window == 0 {
+            return Err("RMS window must be at least one sample".into());
+        }
+        
+        // Prefix sums of squares make each window O(1)
+        let mut cumulative = Vec::with_capacity(data.len() + 1);
+        cumulative.push(0.0);
+        for &value in data {
+            cumulative.push(cumulative[cumulative.len() - 1] + value * value);
+        }
+        
+        let n = data.len();
+        let before = (window - 1) / 2;
+        let after = window / 2;
+        Ok((0..n)
+            .map(|i| {
+                let start = i.saturating_sub(before);
+                let end = (i + after + 1).min(n);
+                ((cumulative[end] - cumulative[start]) / (end - start) as f64).max(0.0).sqrt()
+            })
+            .collect())
+    }
+    
+    /// Indices of local maxima above `min_height`, at least `min_distance` samples apart
+    ///
+    /// When two peaks are closer than `min_distance`, the taller one is kept.
+    /// The first sample of a flat-topped peak is reported.
+    pub fn find_peaks(&self, channel: &str, min_height: f64, min_distance: usize) -> Result<Vec<usize>, Box<dyn Error>> {
+        let data = self
+            .get_channel_data(channel)
+            .ok_or_else(|| format!("Channel not found: {}", channel))?;
+        
+        let candidates: Vec<usize> = (1..data.len().saturating_sub(1))
+            .filter(|&i| data[i] > min_height && data[i] > data[i - 1] && data[i] >= data[i + 1])
+            .collect();
+        
+        // Visit candidates tallest first, suppressing their close neighbours
+        let mut by_height: Vec<usize> = (0..candidates.len()).collect();
+        by_height.sort_by(|&a, &b| {
+            data[candidates[b]]
+                .partial_cmp(&data[candidates[a]])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        
+        let mut suppressed = vec![false; candidates.len()];
+        for &k in &by_height {
+            if suppressed[k] {
+                continue;
+            }
+            let peak = candidates[k];
+            for j in (0..k).rev().take_while(|&j| peak - candidates[j] < min_distance) {
+                suppressed[j] = true;
+            }
+            for j in (k + 1..candidates.len()).take_while(|&j| candidates[j] - peak < min_distance) {
+                suppressed[j] = true;
+            }
+        }
+        
+        Ok(candidates
+            .into_iter()
+            .zip(suppressed)
+            .filter(|(_, removed)| !removed)
+            .map(|(i, _)| i)
+            .collect())
+    }
+    
+    /// Indices of samples at or beyond the amplifier rails `min` and `max`
+    ///
+    /// Returns an empty list if the channel does not exist.
+    pub fn detect_clipping(&self, channel: &str, min: f64, max: f64) -> Vec<usize> {
+        self.get_channel_data(channel)
+            .map(|data| {
+                data.iter()
+                    .enumerate()
+                    .filter(|(_, &value)| value <= min || value >= max)
+                    .map(|(i, _)| i)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+    
+    /// Fraction of a channel's samples at or beyond the rails, as found by `detect_clipping`
+    ///
+    /// Returns 0 for a missing or empty channel.
+    pub fn clipping_fraction(&self, channel: &str, min: f64, max: f64) -> f64 {
+        match self.get_channel_data(channel) {
+            Some(data) if !data.is_empty() => {
+                self.detect_clipping(channel, min, max).len() as f64 / data.len() as f64
+            }
+            _ => 0.0,
+        }
+    }
+    
+    /// Timestamps, or evenly spaced ones starting at 0 when none are set
+    fn effective_timestamps(&self) -> Vec<f64> {
+        if !self.timestamps.is_empty() || self.sampling_rate <= 0.0 {
+            return self.timestamps.clone();
+        }
+        let dt = 1.0 / self.sampling_rate;
+        (0..self.sample_count()).map(|i| dt * i as f64).collect()
+    }
+    
+    /// The `p`-th percentile (0 to 100) of a channel, interpolating linearly between samples
+    ///
+    /// NaN samples are ignored. Returns `None` if the channel does not exist,
+    /// has no numeric samples, or `p` is out of range.
+    pub fn percentile(&self, channel: &str, p: f64) -> Option<f64> {
+        if !(0.0..=100.0).contains(&p) {
+            return None;
+        }
+        let mut values: Vec<f64> = self
+            .get_channel_data(channel)?
+            .iter()
+            .copied()
+            .filter(|v| !v.is_nan())
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(|a, b| a.total_cmp(b));
+        
+        let rank = p / 100.0 * (values.len() - 1) as f64;
+        let lower = rank.floor() as usize;
+        let upper = rank.ceil() as usize;
+        Some(values[lower] + (values[upper] - values[lower]) * (rank - lower as f64))
+    }
+    
+    /// Replace samples outside `[Q1 - factor * IQR, Q3 + factor * IQR]` with NaN
+    ///
+    /// Returns the number of samples replaced; 0 if the channel does not exist.
+    /// Samples that are already NaN are ignored when computing the quartiles.
+    /// `to_json` writes NaN samples as `null` and `from_json` reads them back as NaN.
+    pub fn reject_outliers_iqr(&mut self, channel: &str, factor: f64) -> usize {
+        let (Some(q1), Some(q3)) = (self.percentile(channel, 25.0), self.percentile(channel, 75.0)) else {
+            return 0;
+        };
+        let iqr = q3 - q1;
+        let (low, high) = (q1 - factor * iqr, q3 + factor * iqr);
+        
+        let Some(index) = self.channels.iter().position(|c| c == channel) else {
+            return 0;
+        };
+        let mut rejected = 0;
+        for value in self.data[index].iter_mut() {
+            if *value < low || *value > high {
+                *value = f64::NAN;
+                rejected += 1;
+            }
+        }
+        rejected
+    }
+    
+    /// Check the series against the usual shape of its format
+    pub fn validate_for_format(&self) -> Vec<ValidationWarning> {
+        self.validate_with_expectations(&FormatExpectations::for_format(self.format))
+    }
+    
+    /// Check the series against custom format expectations
+    pub fn validate_with_expectations(&self, expectations: &FormatExpectations) -> Vec<ValidationWarning> {
+        let mut warnings = Vec::new();
+        
+        if self.channels.len() < expectations.min_channels {
+            if self.format == NeuralDataFormat::FMRI {
+                warnings.push(ValidationWarning::FlatFmriSeries);
+            } else {
+                warnings.push(ValidationWarning::LowChannelCount {
+                    format: self.format,
+                    channels: self.channels.len(),
+                    minimum: expectations.min_channels,
+                });
+            }
+        }
+        
+        warnings
+    }
+    
     /// Get data for a specific channel
     pub fn get_channel_data(&self, channel_name: &str) -> Option<&Vec<f64>> {
         let channel_idx = self.channels.iter().position(|c| c == channel_name)?;
         self.data.get(channel_idx)
     }
     
+    /// Convert raw device values to physical units as `value * gain + offset`
+    ///
+    /// A channel missing from `gains` uses a gain of 1 and one missing from
+    /// `offsets` an offset of 0; channels in neither map are left untouched.
+    pub fn apply_gain(&mut self, gains: &HashMap<String, f64>, offsets: &HashMap<String, f64>) {
+        for (name, data) in self.channels.iter().zip(self.data.iter_mut()) {
+            if !gains.contains_key(name) && !offsets.contains_key(name) {
+                continue;
+            }
+            let gain = gains.get(name).copied().unwrap_or(1.0);
+            let offset = offsets.get(name).copied().unwrap_or(0.0);
+            data.iter_mut().for_each(|value| *value = *value * gain + offset);
+        }
+    }
+    
+    /// Set the electrode position of a channel
+    pub fn set_position(&mut self, channel: &str, position: (f64, f64, f64)) -> Result<(), Box<dyn Error>> {
+        if !self.channels.iter().any(|name| name == channel) {
+            return Err(format!("Channel not found: {}", channel).into());
+        }
+        
+        self.channel_positions.insert(channel.to_string(), position);
+        Ok(())
+    }
+    
+    /// Get the electrode position of a channel, if one has been set
+    pub fn position(&self, channel: &str) -> Option<(f64, f64, f64)> {
+        self.channel_positions.get(channel).copied()
+    }
+    
+    /// Scalp map of the values at `sample_index`, interpolated over a `grid x grid` layout
+    ///
+    /// Electrode positions are projected azimuthally (Cz at the centre, the
+    /// equator at radius 1) and values are inverse-distance weighted. The map
+    /// spans the projected electrodes: `map[row][col]` runs left to right and
+    /// front (row 0) to back. Every channel must have a position.
+    pub fn topo_interpolate(&self, sample_index: usize, grid: usize) -> Result<Vec<Vec<f64>>, Box<dyn Error>> {
+        if grid == 0 {
+            return Err("Topographic grid must have at least one cell".into());
+        }
+        if self.channels.is_empty() {
+            return Err("No channels to interpolate".into());
+        }
+        
+        let mut electrodes = Vec::with_capacity(self.channels.len());
+        for (name, data) in self.channels.iter().zip(&self.data) {
+            let position = self
+                .position(name)
+                .ok_or_else(|| format!("Channel {} has no position", name))?;
+            let value = *data
+                .get(sample_index)
+                .ok_or_else(|| format!("Sample {} out of range for channel {}", sample_index, name))?;
+            electrodes.push((project_azimuthal(position), value));
+        }
+        
+        let extent = electrodes
+            .iter()
+            .map(|&((x, y), _)| x.abs().max(y.abs()))
+            .fold(0.0, f64::max);
+        let step = if grid > 1 { 2.0 * extent / (grid - 1) as f64 } else { 0.0 };
+        let origin = if grid > 1 { extent } else { 0.0 };
+        
+        Ok((0..grid)
+            .map(|row| {
+                let y = origin - row as f64 * step;
+                (0..grid)
+                    .map(|col| {
+                        let x = col as f64 * step - origin;
+                        inverse_distance_weighted(&electrodes, (x, y))
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+    
     /// Add metadata
     pub fn add_metadata(&mut self, key: &str, value: &str) {
         self.metadata.insert(key.to_string(), value.to_string());
     }
     
+    /// SHA-256 of the series' canonical JSON, identifying identical recordings
+    pub fn content_hash(&self) -> Result<String, Box<dyn Error>> {
+        Ok(crypto::hash_sha256(&crypto::canonical_json(self)?))
+    }
+    
     /// Serialize to JSON
     pub fn to_json(&self) -> Result<String, Box<dyn Error>> {
         let json = serde_json::to_string(self)?;
@@ -98,6 +652,126 @@ impl NeuralTimeSeries {
         Ok(time_series)
     }
     
+    /// Serialize to JSON with sample values rounded to `decimals` decimal places
+    ///
+    /// This is lossy: each value in `data` may change by up to half a unit in
+    /// the last kept place, and the original precision cannot be recovered.
+    /// Timestamps and metadata are left untouched. Choose `decimals` to stay
+    /// below the recording's noise floor, e.g. 2 for microvolt EEG.
+    pub fn to_json_rounded(&self, decimals: u8) -> Result<String, Box<dyn Error>> {
+        let scale = 10f64.powi(decimals as i32);
+        let round = |value: f64| {
+            let scaled = value * scale;
+            // Beyond 2^52 an f64 has no fractional digits left to round away
+            if !scaled.is_finite() || scaled.abs() >= 2f64.powi(52) {
+                value
+            } else {
+                scaled.round() / scale
+            }
+        };
+        
+        let rounded = NeuralTimeSeries {
+            format: self.format,
+            sampling_rate: self.sampling_rate,
+            channels: self.channels.clone(),
+            timestamps: self.timestamps.clone(),
+            data: self.data.iter().map(|channel| channel.iter().map(|&v| round(v)).collect()).collect(),
+            units: self.units.clone(),
+            metadata: self.metadata.clone(),
+            channel_positions: self.channel_positions.clone(),
+        };
+        rounded.to_json()
+    }
+    
+    /// Approximate length of `to_json` in bytes, without serializing the samples
+    ///
+    /// Field names, channel names and metadata are serialized exactly. The
+    /// width of each number is estimated from up to `SIZE_ESTIMATE_SAMPLES`
+    /// evenly spaced values per array, since full-precision `f64` values take
+    /// around 18 characters in JSON while quantized values take far fewer.
+    pub fn estimated_size_bytes(&self) -> usize {
+        let skeleton = NeuralTimeSeries {
+            format: self.format,
+            sampling_rate: self.sampling_rate,
+            channels: self.channels.clone(),
+            timestamps: Vec::new(),
+            data: vec![Vec::new(); self.data.len()],
+            units: self.units.clone(),
+            metadata: self.metadata.clone(),
+            channel_positions: self.channel_positions.clone(),
+        };
+        let overhead = serde_json::to_string(&skeleton).map(|json| json.len()).unwrap_or(0);
+        
+        overhead
+            + estimated_array_bytes(&self.timestamps)
+            + self.data.iter().map(|channel| estimated_array_bytes(channel)).sum::<usize>()
+    }
+    
+    /// Approximate length of `to_json_compressed` in bytes
+    ///
+    /// Compresses a leading segment of about `COMPRESSION_PROBE_BYTES` of JSON
+    /// and scales its compression ratio to the whole series, as the ratio
+    /// depends heavily on the signal and its precision.
+    pub fn estimated_compressed_size_bytes(&self) -> usize {
+        let estimate = self.estimated_size_bytes();
+        let total = self.sample_count();
+        let probe_samples = (total * COMPRESSION_PROBE_BYTES / estimate.max(1)).clamp(1, total.max(1));
+        
+        let probe = match self.split_fixed(probe_samples).into_iter().next() {
+            Some(probe) => probe,
+            None => return self.to_json_compressed().map(|c| c.len()).unwrap_or(estimate),
+        };
+        match (probe.to_json(), probe.to_json_compressed()) {
+            (Ok(json), Ok(compressed)) => {
+                (estimate as f64 * compressed.len() as f64 / json.len() as f64).ceil() as usize
+            }
+            _ => estimate,
+        }
+    }
+    
+    /// Serialize to gzip-compressed JSON, wrapped in base64
+    ///
+    /// For a 32-channel, 10 s EEG recording at full f64 precision the output
+    /// is roughly 40% smaller than `to_json`, even after the base64 overhead;
+    /// quantized or low-precision data compresses considerably further.
+    pub fn to_json_compressed(&self) -> Result<String, Box<dyn Error>> {
+        let json = serde_json::to_vec(self)?;
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&json)?;
+        let compressed = encoder.finish()?;
+        Ok(BASE64.encode(compressed))
+    }
+    
+    /// Deserialize from the output of `to_json_compressed`
+    pub fn from_json_compressed(encoded: &str) -> Result<Self, Box<dyn Error>> {
+        let compressed = BASE64.decode(encoded)?;
+        let mut json = Vec::new();
+        GzDecoder::new(compressed.as_slice()).read_to_end(&mut json)?;
+        let time_series: NeuralTimeSeries = serde_json::from_slice(&json)?;
+        Ok(time_series)
+    }
+    
+    /// Serialize to the layout of an MNE-Python `Raw` object
+    ///
+    /// Produces `{"info": {"ch_names", "sfreq", "ch_types"}, "data", "units"}`
+    /// where `data` is channel x time, ready for `mne.create_info` and
+    /// `mne.io.RawArray` in a thin Python shim. Samples are written in the
+    /// series' own `units`; MNE expects SI units, so the shim should rescale
+    /// (e.g. by 1e-6 for microvolts).
+    pub fn to_mne_json(&self) -> Result<String, Box<dyn Error>> {
+        let ch_type = self.format.mne_channel_type();
+        let mne = serde_json::json!({
+            "info": {
+                "ch_names": self.channels,
+                "sfreq": self.sampling_rate,
+                "ch_types": vec![ch_type; self.channels.len()],
+            },
+            "data": self.data,
+            "units": self.units,
+        });
+        Ok(serde_json::to_string(&mne)?)
+    }
+    
     /// Calculate basic statistics for a channel
     pub fn calculate_channel_stats(&self, channel_name: &str) -> Option<ChannelStatistics> {
         let data = self.get_channel_data(channel_name)?;
@@ -134,111 +808,2308 @@ impl NeuralTimeSeries {
             std_dev,
         })
     }
-}
-
-/// Statistics for a neural data channel
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ChannelStatistics {
-    pub channel: String,
-    pub min: f64,
-    pub max: f64,
-    pub mean: f64,
-    pub std_dev: f64,
-}
-
-/// Represents metadata for a brain imaging study
-#[derive(Debug, Serialize, Deserialize)]
-pub struct BrainStudyMetadata {
-    pub subject_id: String,
-    pub age: Option<u8>,
-    pub sex: Option<String>,
-    pub diagnosis: Option<String>,
-    pub study_date: String,
-    pub experiment_type: String,
-    pub institution: String,
-    pub researchers: Vec<String>,
-    pub equipment: HashMap<String, String>,
-    pub notes: Option<String>,
-    pub protocol_id: Option<String>,
-}
-
-impl BrainStudyMetadata {
-    pub fn new(subject_id: &str, experiment_type: &str, institution: &str) -> Self {
-        BrainStudyMetadata {
-            subject_id: subject_id.to_string(),
-            age: None,
-            sex: None,
-            diagnosis: None,
-            study_date: chrono::Local::now().format("%Y-%m-%d").to_string(),
-            experiment_type: experiment_type.to_string(),
-            institution: institution.to_string(),
-            researchers: Vec::new(),
-            equipment: HashMap::new(),
-            notes: None,
-            protocol_id: None,
+    
+    /// Outlier-resistant z-scores: `(x - median) / (1.4826 * MAD)`
+    ///
+    /// MAD is the median absolute deviation from the median; the 1.4826 factor
+    /// makes it consistent with the standard deviation for normal data. NaN
+    /// samples are ignored when computing the median and stay NaN. A zero MAD
+    /// yields all zeros.
+    pub fn robust_zscore(&self, channel: &str) -> Result<Vec<f64>, Box<dyn Error>> {
+        let data = self
+            .get_channel_data(channel)
+            .ok_or_else(|| format!("Channel not found: {}", channel))?;
+        let center = median(data.iter().copied())
+            .ok_or_else(|| format!("Channel {} has no samples", channel))?;
+        let mad = median(data.iter().map(|v| (v - center).abs())).unwrap_or(0.0);
+        
+        if mad == 0.0 {
+            return Ok(vec![0.0; data.len()]);
         }
+        let scale = 1.4826 * mad;
+        Ok(data.iter().map(|v| (v - center) / scale).collect())
     }
     
-    pub fn add_researcher(&mut self, name: &str) {
-        self.researchers.push(name.to_string());
+    /// Power of a channel within a frequency band, from its periodogram
+    ///
+    /// The channel mean is removed first so DC does not leak into low bands.
+    /// Returns the one-sided power spectral density integrated over
+    /// `band.0..=band.1` Hz, in squared channel units.
+    pub fn band_power(&self, channel: &str, band: (f64, f64)) -> Result<f64, Box<dyn Error>> {
+        self.validate_band(band)?;
+        let (psd, df) = self.periodogram(channel)?;
+        
+        let power = psd
+            .iter()
+            .enumerate()
+            .filter(|(k, _)| {
+                let freq = *k as f64 * df;
+                freq >= band.0 && freq <= band.1
+            })
+            .map(|(_, density)| density * df)
+            .sum();
+        
+        Ok(power)
     }
     
-    pub fn add_equipment(&mut self, name: &str, details: &str) {
-        self.equipment.insert(name.to_string(), details.to_string());
+    /// Ratio of band power in `numerator` to band power in `denominator`
+    ///
+    /// Both powers come from `band_power`. Fails if the denominator band has
+    /// no power.
+    pub fn band_power_ratio(&self, channel: &str, numerator: (f64, f64), denominator: (f64, f64)) -> Result<f64, Box<dyn Error>> {
+        let numerator_power = self.band_power(channel, numerator)?;
+        let denominator_power = self.band_power(channel, denominator)?;
+        if denominator_power <= 0.0 {
+            return Err(format!("Band {:?} has no power", denominator).into());
+        }
+        
+        Ok(numerator_power / denominator_power)
     }
     
-    pub fn to_json(&self) -> Result<String, Box<dyn Error>> {
-        let json = serde_json::to_string(self)?;
-        Ok(json)
+    /// Theta/beta power ratio (`THETA_BAND` over `BETA_BAND`), a common attention marker
+    pub fn theta_beta_ratio(&self, channel: &str) -> Result<f64, Box<dyn Error>> {
+        self.band_power_ratio(channel, THETA_BAND, BETA_BAND)
     }
     
-    pub fn from_json(json: &str) -> Result<Self, Box<dyn Error>> {
-        let metadata: BrainStudyMetadata = serde_json::from_str(json)?;
-        Ok(metadata)
+    /// Frequency of the strongest periodogram bin, ignoring DC
+    pub fn dominant_frequency(&self, channel: &str) -> Result<f64, Box<dyn Error>> {
+        let (psd, df) = self.periodogram(channel)?;
+        
+        let (peak, _) = psd
+            .iter()
+            .enumerate()
+            .skip(1)
+            .fold((None, f64::NEG_INFINITY), |(best, max), (k, &density)| {
+                if density > max { (Some(k), density) } else { (best, max) }
+            });
+        let peak = peak.ok_or_else(|| format!("Channel {} is too short for a spectrum", channel))?;
+        
+        Ok(peak as f64 * df)
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_create_neural_time_series() {
-        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 256.0, "microvolts");
+    
+    /// Shannon entropy in bits of the channel's amplitude distribution
+    ///
+    /// Samples are histogrammed into `bins` equal-width bins spanning the
+    /// channel's min–max range. The result lies in `[0, log2(bins)]`.
+    pub fn shannon_entropy(&self, channel: &str, bins: usize) -> Result<f64, Box<dyn Error>> {
+        let data = self
+            .get_channel_data(channel)
+            .ok_or_else(|| format!("Channel not found: {}", channel))?;
+        if data.is_empty() {
+            return Err(format!("Channel {} has no samples", channel).into());
+        }
+        if bins == 0 {
+            return Err("Histogram needs at least one bin".into());
+        }
         
-        // Add timestamps
-        ts.generate_timestamps(0.0, 5);
-        assert_eq!(ts.timestamps, vec![0.0, 0.00390625, 0.0078125, 0.01171875, 0.015625]);
+        let min = data.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = data.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let width = (max - min) / bins as f64;
         
-        // Add a channel
-        let data = vec![1.0, 2.0, 3.0, 4.0, 5.0];
-        ts.add_channel("Fz", data.clone()).unwrap();
+        let mut counts = vec![0usize; bins];
+        for &value in data {
+            let bin = if width > 0.0 { ((value - min) / width) as usize } else { 0 };
+            counts[bin.min(bins - 1)] += 1;
+        }
         
-        assert_eq!(ts.channels.len(), 1);
-        assert_eq!(ts.data.len(), 1);
-        assert_eq!(ts.get_channel_data("Fz").unwrap(), &data);
+        Ok(entropy_bits(counts.iter().map(|&count| count as f64)))
+    }
+    
+    /// Spectral entropy in bits: the Shannon entropy of the normalized PSD, excluding DC
+    ///
+    /// A pure tone concentrates power in one bin and scores near 0, while
+    /// white noise approaches `log2` of the number of frequency bins.
+    pub fn spectral_entropy(&self, channel: &str) -> Result<f64, Box<dyn Error>> {
+        let (psd, _) = self.periodogram(channel)?;
+        if psd.len() < 2 {
+            return Err(format!("Channel {} is too short for a spectrum", channel).into());
+        }
         
-        // Add metadata
-        ts.add_metadata("subject", "S001");
-        assert_eq!(ts.metadata.get("subject").unwrap(), "S001");
+        Ok(entropy_bits(psd[1..].iter().copied()))
     }
     
-    #[test]
-    fn test_calculate_statistics() {
-        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 256.0, "microvolts");
+    /// One-sided power spectral density of a channel and its bin width in Hz
+    ///
+    /// The channel mean is removed before the transform.
+    fn periodogram(&self, channel: &str) -> Result<(Vec<f64>, f64), Box<dyn Error>> {
+        let data = self
+            .get_channel_data(channel)
+            .ok_or_else(|| format!("Channel not found: {}", channel))?;
+        if data.is_empty() {
+            return Err(format!("Channel {} has no samples", channel).into());
+        }
         
-        // Add a channel
-        let data = vec![1.0, 2.0, 3.0, 4.0, 5.0];
-        ts.add_channel("Fz", data).unwrap();
+        let n = data.len();
+        let mean = data.iter().sum::<f64>() / n as f64;
+        let mut buffer: Vec<Complex<f64>> = data.iter().map(|&v| Complex::new(v - mean, 0.0)).collect();
+        FftPlanner::new().plan_fft_forward(n).process(&mut buffer);
         
-        let stats = ts.calculate_channel_stats("Fz").unwrap();
+        let psd = buffer
+            .iter()
+            .enumerate()
+            .take(n / 2 + 1)
+            .map(|(k, value)| {
+                // Bins other than DC and Nyquist also carry the mirrored negative frequency
+                let scale = if k == 0 || (n % 2 == 0 && k == n / 2) { 1.0 } else { 2.0 };
+                scale * value.norm_sqr() / (self.sampling_rate * n as f64)
+            })
+            .collect();
         
-        assert_eq!(stats.min, 1.0);
-        assert_eq!(stats.max, 5.0);
-        assert_eq!(stats.mean, 3.0);
-        assert!(stats.std_dev - 1.4142135 < 0.0001);
+        Ok((psd, self.sampling_rate / n as f64))
     }
-
+    
+    /// Normalized autocorrelation of a channel for lags `0..=max_lag` samples
+    ///
+    /// Uses the biased estimator on the mean-removed signal, so lag 0 is 1.0
+    /// and longer lags shrink toward zero as the overlap shortens. Computed
+    /// with a zero-padded FFT in `O(n log n)`.
+    pub fn autocorrelation(&self, channel: &str, max_lag: usize) -> Result<Vec<f64>, Box<dyn Error>> {
+        let data = self
+            .get_channel_data(channel)
+            .ok_or_else(|| format!("Channel not found: {}", channel))?;
+        let n = data.len();
+        if max_lag >= n {
+            return Err(format!("max_lag {} must be less than the {} samples", max_lag, n).into());
+        }
+        
+        // Padding to at least 2n avoids circular wrap-around
+        let size = (2 * n).next_power_of_two();
+        let mean = data.iter().sum::<f64>() / n as f64;
+        let mut buffer: Vec<Complex<f64>> = data
+            .iter()
+            .map(|&v| Complex::new(v - mean, 0.0))
+            .chain(std::iter::repeat(Complex::new(0.0, 0.0)))
+            .take(size)
+            .collect();
+        
+        let mut planner = FftPlanner::new();
+        planner.plan_fft_forward(size).process(&mut buffer);
+        for value in buffer.iter_mut() {
+            *value = Complex::new(value.norm_sqr(), 0.0);
+        }
+        planner.plan_fft_inverse(size).process(&mut buffer);
+        
+        let zero_lag = buffer[0].re;
+        if zero_lag <= f64::EPSILON * n as f64 {
+            return Err(format!("Channel {} is constant; autocorrelation is undefined", channel).into());
+        }
+        Ok(buffer[..=max_lag].iter().map(|value| value.re / zero_lag).collect())
+    }
+    
+    /// Lag (in samples) and value of the peak normalized cross-correlation between two channels
+    ///
+    /// A positive lag means `ch_b` follows `ch_a`, i.e. `b[i + lag]` matches
+    /// `a[i]`. For each lag in `-max_lag..=max_lag` the Pearson correlation of
+    /// the overlapping samples is computed, so the value lies in `[-1, 1]`.
+    pub fn cross_correlation_lag(&self, ch_a: &str, ch_b: &str, max_lag: usize) -> Result<(i64, f64), Box<dyn Error>> {
+        let a = self
+            .get_channel_data(ch_a)
+            .ok_or_else(|| format!("Channel not found: {}", ch_a))?;
+        let b = self
+            .get_channel_data(ch_b)
+            .ok_or_else(|| format!("Channel not found: {}", ch_b))?;
+        let n = a.len().min(b.len());
+        if max_lag + 2 > n {
+            return Err(format!("max_lag {} leaves fewer than 2 overlapping samples out of {}", max_lag, n).into());
+        }
+        
+        let mut best: Option<(i64, f64)> = None;
+        for lag in -(max_lag as i64)..=max_lag as i64 {
+            let shift = lag.unsigned_abs() as usize;
+            let (xs, ys) = if lag >= 0 {
+                (&a[..n - shift], &b[shift..n])
+            } else {
+                (&a[shift..n], &b[..n - shift])
+            };
+            
+            let Some(correlation) = pearson(xs, ys) else { continue };
+            if best.is_none_or(|(_, max)| correlation > max) {
+                best = Some((lag, correlation));
+            }
+        }
+        
+        best.ok_or_else(|| "Channels are constant; correlation is undefined".into())
+    }
+    
+    /// Pearson correlation between two channels in sliding windows
+    ///
+    /// Windows of `window` samples start every `step` samples; a trailing
+    /// partial window is dropped. A window where either channel is constant
+    /// yields NaN.
+    pub fn rolling_correlation(&self, ch_a: &str, ch_b: &str, window: usize, step: usize) -> Result<Vec<f64>, Box<dyn Error>> {
+        let a = self
+            .get_channel_data(ch_a)
+            .ok_or_else(|| format!("Channel not found: {}", ch_a))?;
+        let b = self
+            .get_channel_data(ch_b)
+            .ok_or_else(|| format!("Channel not found: {}", ch_b))?;
+        let n = a.len().min(b.len());
+        if window < 2 || window > n {
+            return Err(format!("Window must be between 2 and {} samples, got {}", n, window).into());
+        }
+        if step == 0 {
+            return Err("Step must be at least one sample".into());
+        }
+        
+        Ok((0..=n - window)
+            .step_by(step)
+            .map(|start| pearson(&a[start..start + window], &b[start..start + window]).unwrap_or(f64::NAN))
+            .collect())
+    }
+    
+    /// Phase-locking value between two channels within a frequency band
+    ///
+    /// Both channels are band-limited to `band` and their instantaneous phase
+    /// is taken from the analytic signal. The result is the mean resultant
+    /// length of the phase differences: 1.0 for a constant phase lag, near 0
+    /// for unrelated phases, independent of amplitude.
+    pub fn phase_locking_value(&self, ch_a: &str, ch_b: &str, band: (f64, f64)) -> Result<f64, Box<dyn Error>> {
+        self.validate_band(band)?;
+        let phase_a = self.band_phase(ch_a, band)?;
+        let phase_b = self.band_phase(ch_b, band)?;
+        let n = phase_a.len().min(phase_b.len());
+        if n == 0 {
+            return Err("Channels have no samples".into());
+        }
+        
+        let (sum_cos, sum_sin) = phase_a
+            .iter()
+            .zip(&phase_b)
+            .fold((0.0, 0.0), |(c, s), (a, b)| (c + (a - b).cos(), s + (a - b).sin()));
+        
+        Ok((sum_cos * sum_cos + sum_sin * sum_sin).sqrt() / n as f64)
+    }
+    
+    /// Instantaneous phase of a channel restricted to `band`, via the Hilbert transform
+    ///
+    /// Computed in one pass: the spectrum is zeroed outside the positive
+    /// frequencies of `band`, the kept bins are doubled and the inverse
+    /// transform gives the band-limited analytic signal.
+    fn band_phase(&self, channel: &str, band: (f64, f64)) -> Result<Vec<f64>, Box<dyn Error>> {
+        let data = self
+            .get_channel_data(channel)
+            .ok_or_else(|| format!("Channel not found: {}", channel))?;
+        let n = data.len();
+        
+        let mut buffer: Vec<Complex<f64>> = data.iter().map(|&v| Complex::new(v, 0.0)).collect();
+        let mut planner = FftPlanner::new();
+        planner.plan_fft_forward(n).process(&mut buffer);
+        
+        let df = self.sampling_rate / n as f64;
+        for (k, value) in buffer.iter_mut().enumerate() {
+            let freq = k as f64 * df;
+            let positive = k > 0 && 2 * k < n;
+            *value = if positive && freq >= band.0 && freq <= band.1 {
+                *value * 2.0
+            } else {
+                Complex::new(0.0, 0.0)
+            };
+        }
+        planner.plan_fft_inverse(n).process(&mut buffer);
+        
+        Ok(buffer.iter().map(|value| value.arg()).collect())
+    }
+    
+    /// Signal-to-noise ratio in dB, as band power in `signal_band` over `noise_band`
+    pub fn snr(&self, channel: &str, signal_band: (f64, f64), noise_band: (f64, f64)) -> Result<f64, Box<dyn Error>> {
+        if signal_band.0 < noise_band.1 && noise_band.0 < signal_band.1 {
+            return Err(format!(
+                "Signal band {:?} overlaps noise band {:?}",
+                signal_band, noise_band
+            ).into());
+        }
+        
+        let signal_power = self.band_power(channel, signal_band)?;
+        let noise_power = self.band_power(channel, noise_band)?;
+        if noise_power <= 0.0 {
+            return Err("Noise band has no power".into());
+        }
+        
+        Ok(10.0 * (signal_power / noise_power).log10())
+    }
+    
+    /// Filter a channel with an FIR kernel using FFT overlap-add convolution
+    ///
+    /// The filter is causal: the output has the same length as the input and
+    /// sample `i` depends only on input samples `0..=i`, so a symmetric kernel
+    /// delays the signal by `(taps.len() - 1) / 2` samples.
+    pub fn fft_filter(&self, channel: &str, taps: &[f64]) -> Result<Vec<f64>, Box<dyn Error>> {
+        let data = self
+            .get_channel_data(channel)
+            .ok_or_else(|| format!("Channel not found: {}", channel))?;
+        if taps.is_empty() {
+            return Err("FIR kernel must have at least one tap".into());
+        }
+        
+        let n = data.len();
+        let m = taps.len();
+        let fft_len = (2 * m).next_power_of_two().max(256);
+        let block_len = fft_len - m + 1;
+        
+        let mut planner = FftPlanner::new();
+        let forward = planner.plan_fft_forward(fft_len);
+        let inverse = planner.plan_fft_inverse(fft_len);
+        
+        let mut kernel: Vec<Complex<f64>> = taps.iter().map(|&t| Complex::new(t, 0.0)).collect();
+        kernel.resize(fft_len, Complex::new(0.0, 0.0));
+        forward.process(&mut kernel);
+        
+        let mut output = vec![0.0; n];
+        let mut buffer = vec![Complex::new(0.0, 0.0); fft_len];
+        for start in (0..n).step_by(block_len) {
+            let block = &data[start..(start + block_len).min(n)];
+            for (i, slot) in buffer.iter_mut().enumerate() {
+                *slot = Complex::new(block.get(i).copied().unwrap_or(0.0), 0.0);
+            }
+            
+            forward.process(&mut buffer);
+            for (value, k) in buffer.iter_mut().zip(&kernel) {
+                *value *= k;
+            }
+            inverse.process(&mut buffer);
+            
+            for (i, value) in buffer.iter().enumerate() {
+                match output.get_mut(start + i) {
+                    Some(out) => *out += value.re / fft_len as f64,
+                    None => break,
+                }
+            }
+        }
+        
+        Ok(output)
+    }
+    
+    /// Design a linear-phase low-pass FIR kernel (Hamming-windowed sinc)
+    ///
+    /// The kernel is normalized to unity gain at DC.
+    pub fn design_lowpass_fir(&self, cutoff_hz: f64, num_taps: usize) -> Result<Vec<f64>, Box<dyn Error>> {
+        self.validate_band((0.0, cutoff_hz))?;
+        if num_taps == 0 {
+            return Err("FIR kernel must have at least one tap".into());
+        }
+        
+        let fc = cutoff_hz / self.sampling_rate;
+        let center = (num_taps - 1) as f64 / 2.0;
+        let mut taps: Vec<f64> = (0..num_taps)
+            .map(|i| {
+                let x = i as f64 - center;
+                let sinc = if x == 0.0 {
+                    2.0 * fc
+                } else {
+                    (2.0 * std::f64::consts::PI * fc * x).sin() / (std::f64::consts::PI * x)
+                };
+                let window = if num_taps == 1 {
+                    1.0
+                } else {
+                    0.54 - 0.46 * (2.0 * std::f64::consts::PI * i as f64 / (num_taps - 1) as f64).cos()
+                };
+                sinc * window
+            })
+            .collect();
+        
+        let gain: f64 = taps.iter().sum();
+        taps.iter_mut().for_each(|t| *t /= gain);
+        Ok(taps)
+    }
+    
+    /// Check that a frequency band is ordered and lies below the Nyquist frequency
+    fn validate_band(&self, band: (f64, f64)) -> Result<(), Box<dyn Error>> {
+        let nyquist = self.sampling_rate / 2.0;
+        if band.0 < 0.0 || band.0 >= band.1 || band.1 > nyquist {
+            return Err(format!(
+                "Invalid band {:?}: expected 0 <= low < high <= {} Hz (Nyquist)",
+                band, nyquist
+            ).into());
+        }
+        Ok(())
+    }
+    
+    /// Channel means, mean-centered data and the channel covariance matrix
+    ///
+    /// Assumes every channel has the same, non-zero number of samples.
+    fn centered_covariance(&self) -> (Vec<f64>, Vec<Vec<f64>>, Vec<Vec<f64>>) {
+        let n_channels = self.data.len();
+        let n_samples = self.data[0].len();
+        
+        let means: Vec<f64> = self.data
+            .iter()
+            .map(|channel| channel.iter().sum::<f64>() / n_samples as f64)
+            .collect();
+        let centered: Vec<Vec<f64>> = self.data
+            .iter()
+            .zip(&means)
+            .map(|(channel, mean)| channel.iter().map(|v| v - mean).collect())
+            .collect();
+        
+        let mut covariance = vec![vec![0.0; n_channels]; n_channels];
+        for i in 0..n_channels {
+            for j in i..n_channels {
+                let c = dot(&centered[i], &centered[j]) / n_samples as f64;
+                covariance[i][j] = c;
+                covariance[j][i] = c;
+            }
+        }
+        
+        (means, centered, covariance)
+    }
+    
+    /// Principal component analysis across channels
+    ///
+    /// Returns the `n_components` directions of greatest variance, the data
+    /// projected onto them and the fraction of the total variance each explains.
+    pub fn pca(&self, n_components: usize) -> Result<PcaResult, Box<dyn Error>> {
+        let n_channels = self.data.len();
+        if n_components == 0 || n_components > n_channels {
+            return Err(format!("n_components must be between 1 and {}, got {}", n_channels, n_components).into());
+        }
+        
+        let n_samples = self.data[0].len();
+        if self.data.iter().any(|channel| channel.len() != n_samples) {
+            return Err("All channels must have the same number of samples".into());
+        }
+        if n_samples < 2 {
+            return Err("PCA requires at least two samples".into());
+        }
+        
+        let (means, centered, covariance) = self.centered_covariance();
+        let (eigenvalues, eigenvectors) = symmetric_eigen(&covariance);
+        let mut order: Vec<usize> = (0..n_channels).collect();
+        order.sort_by(|&a, &b| eigenvalues[b].total_cmp(&eigenvalues[a]));
+        order.truncate(n_components);
+        
+        // Eigenvalues of a covariance matrix are non-negative up to rounding
+        let total_variance: f64 = eigenvalues.iter().map(|v| v.max(0.0)).sum();
+        let explained_variance_ratio = order
+            .iter()
+            .map(|&k| if total_variance > 0.0 { eigenvalues[k].max(0.0) / total_variance } else { 0.0 })
+            .collect();
+        let components: Vec<Vec<f64>> = order
+            .iter()
+            .map(|&k| (0..n_channels).map(|c| eigenvectors[c][k]).collect())
+            .collect();
+        let projections = mat_mul(&components, &centered);
+        
+        Ok(PcaResult {
+            channels: self.channels.clone(),
+            means,
+            components,
+            projections,
+            explained_variance_ratio,
+        })
+    }
+    
+    /// Decompose the channels into independent components using FastICA
+    ///
+    /// The data is centered, whitened via PCA (keeping the `n_components`
+    /// strongest directions), then each component is found by the fixed-point
+    /// iteration with a `tanh` nonlinearity and deflationary orthogonalization.
+    pub fn ica(&self, n_components: usize) -> Result<IcaResult, Box<dyn Error>> {
+        let n_channels = self.data.len();
+        if n_components == 0 || n_components > n_channels {
+            return Err(format!("n_components must be between 1 and {}, got {}", n_channels, n_components).into());
+        }
+        
+        let n_samples = self.data[0].len();
+        if self.data.iter().any(|channel| channel.len() != n_samples) {
+            return Err("All channels must have the same number of samples".into());
+        }
+        if n_samples < 2 {
+            return Err("ICA requires at least two samples".into());
+        }
+        
+        // Whitening via PCA of the channel covariance
+        let (means, centered, covariance) = self.centered_covariance();
+        let (eigenvalues, eigenvectors) = symmetric_eigen(&covariance);
+        let mut order: Vec<usize> = (0..n_channels).collect();
+        order.sort_by(|&a, &b| eigenvalues[b].total_cmp(&eigenvalues[a]));
+        order.truncate(n_components);
+        
+        if order.iter().any(|&k| eigenvalues[k] <= 1e-12) {
+            return Err("Data is rank deficient for the requested number of components".into());
+        }
+        
+        // whitening: n_components x n_channels, dewhitening: n_channels x n_components
+        let whitening: Vec<Vec<f64>> = order
+            .iter()
+            .map(|&k| (0..n_channels).map(|c| eigenvectors[c][k] / eigenvalues[k].sqrt()).collect())
+            .collect();
+        let dewhitening: Vec<Vec<f64>> = (0..n_channels)
+            .map(|c| order.iter().map(|&k| eigenvectors[c][k] * eigenvalues[k].sqrt()).collect())
+            .collect();
+        let whitened = mat_mul(&whitening, &centered);
+        
+        // Fixed-point iteration, one component at a time
+        let mut unmixing: Vec<Vec<f64>> = Vec::with_capacity(n_components);
+        for p in 0..n_components {
+            let mut w: Vec<f64> = (0..n_components)
+                .map(|i| if i == p { 1.0 } else { 0.1 })
+                .collect();
+            normalize(&mut w);
+            
+            for _ in 0..ICA_MAX_ITERATIONS {
+                let projection: Vec<f64> = (0..n_samples)
+                    .map(|t| (0..n_components).map(|i| w[i] * whitened[i][t]).sum())
+                    .collect();
+                let g: Vec<f64> = projection.iter().map(|u| u.tanh()).collect();
+                let g_prime_mean = g.iter().map(|v| 1.0 - v * v).sum::<f64>() / n_samples as f64;
+                
+                let mut next: Vec<f64> = (0..n_components)
+                    .map(|i| dot(&whitened[i], &g) / n_samples as f64 - g_prime_mean * w[i])
+                    .collect();
+                for previous in &unmixing {
+                    let overlap = dot(&next, previous);
+                    for (value, prev) in next.iter_mut().zip(previous) {
+                        *value -= overlap * prev;
+                    }
+                }
+                normalize(&mut next);
+                
+                let converged = (dot(&next, &w).abs() - 1.0).abs() < ICA_TOLERANCE;
+                w = next;
+                if converged {
+                    break;
+                }
+            }
+            unmixing.push(w);
+        }
+        
+        let components = mat_mul(&unmixing, &whitened);
+        // The unmixing matrix is orthogonal, so its inverse is its transpose
+        let mixing: Vec<Vec<f64>> = dewhitening
+            .iter()
+            .map(|row| (0..n_components).map(|k| dot(row, &unmixing[k])).collect())
+            .collect();
+        
+        Ok(IcaResult {
+            channels: self.channels.clone(),
+            means,
+            mixing,
+            components,
+        })
+    }
+}
+
+/// Maximum fixed-point iterations per ICA component
+const ICA_MAX_ITERATIONS: usize = 200;
+
+/// Convergence threshold for the ICA fixed-point iteration
+const ICA_TOLERANCE: f64 = 1e-8;
+
+/// Result of an independent component analysis
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IcaResult {
+    pub channels: Vec<String>,
+    /// Per-channel means removed before decomposition
+    pub means: Vec<f64>,
+    /// Mixing matrix (channel x component)
+    pub mixing: Vec<Vec<f64>>,
+    /// Component time courses (component x time)
+    pub components: Vec<Vec<f64>>,
+}
+
+impl IcaResult {
+    /// Project back to channel space with the given components zeroed out
+    ///
+    /// Returns the cleaned data matrix (channel x time), ready to replace
+    /// `NeuralTimeSeries::data`.
+    pub fn reconstruct_without(&self, components: &[usize]) -> Vec<Vec<f64>> {
+        let n_samples = self.components.first().map_or(0, |c| c.len());
+        
+        self.mixing
+            .iter()
+            .zip(&self.means)
+            .map(|(weights, mean)| {
+                (0..n_samples)
+                    .map(|t| {
+                        let mixed: f64 = weights
+                            .iter()
+                            .enumerate()
+                            .filter(|(k, _)| !components.contains(k))
+                            .map(|(k, weight)| weight * self.components[k][t])
+                            .sum();
+                        mixed + mean
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Result of a principal component analysis
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PcaResult {
+    pub channels: Vec<String>,
+    /// Per-channel means removed before decomposition
+    pub means: Vec<f64>,
+    /// Unit-length principal directions (component x channel), strongest first
+    pub components: Vec<Vec<f64>>,
+    /// Centered data projected onto each component (component x time)
+    pub projections: Vec<Vec<f64>>,
+    /// Fraction of the total variance explained by each component
+    pub explained_variance_ratio: Vec<f64>,
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn normalize(v: &mut [f64]) {
+    let norm = dot(v, v).sqrt();
+    if norm > 0.0 {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+/// Multiply an (m x k) matrix by a (k x n) matrix
+fn mat_mul(a: &[Vec<f64>], b: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let n = b.first().map_or(0, |row| row.len());
+    a.iter()
+        .map(|row| {
+            (0..n)
+                .map(|j| row.iter().zip(b).map(|(x, b_row)| x * b_row[j]).sum())
+                .collect()
+        })
+        .collect()
+}
+
+/// Eigen-decomposition of a symmetric matrix
+///
+/// Returns the eigenvalues and a matrix whose columns are the eigenvectors.
+fn symmetric_eigen(matrix: &[Vec<f64>]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let n = matrix.len();
+    let eigen = to_matrix(matrix).symmetric_eigen();
+    let eigenvectors = (0..n)
+        .map(|i| (0..n).map(|j| eigen.eigenvectors[(i, j)]).collect())
+        .collect();
+    (eigen.eigenvalues.iter().copied().collect(), eigenvectors)
+}
+
+/// Copy a square row-major matrix into an `nalgebra` matrix
+fn to_matrix(rows: &[Vec<f64>]) -> DMatrix<f64> {
+    DMatrix::from_fn(rows.len(), rows.len(), |i, j| rows[i][j])
+}
+
+/// Statistics for a neural data channel
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChannelStatistics {
+    pub channel: String,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+/// Key of a cached analysis: content hash, operation name and serialized parameters
+type AnalysisKey = (String, String, String);
+
+/// Memoized analysis results for recordings that have already been processed
+///
+/// Results are stored as JSON values, so any serializable result (PSDs,
+/// statistics, band powers) can share one cache. When `max_entries` is
+/// reached the oldest entry is evicted.
+#[derive(Debug)]
+pub struct AnalysisCache {
+    entries: HashMap<AnalysisKey, serde_json::Value>,
+    order: VecDeque<AnalysisKey>,
+    max_entries: usize,
+}
+
+impl AnalysisCache {
+    /// Create an empty cache holding at most `max_entries` results
+    pub fn new(max_entries: usize) -> Self {
+        AnalysisCache {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            max_entries,
+        }
+    }
+    
+    /// Return the cached result for this key, or compute and cache it
+    ///
+    /// `params` should uniquely describe the operation's parameters, e.g. as
+    /// canonical JSON. Errors from `compute` are returned and not cached.
+    pub fn get_or_compute<T, F>(
+        &mut self,
+        content_hash: &str,
+        operation: &str,
+        params: &str,
+        compute: F,
+    ) -> Result<T, Box<dyn Error>>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Result<T, Box<dyn Error>>,
+    {
+        let key = (content_hash.to_string(), operation.to_string(), params.to_string());
+        if let Some(cached) = self.entries.get(&key) {
+            return Ok(serde_json::from_value(cached.clone())?);
+        }
+        
+        let result = compute()?;
+        if self.max_entries > 0 {
+            if self.entries.len() >= self.max_entries {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.entries.remove(&oldest);
+                }
+            }
+            self.entries.insert(key.clone(), serde_json::to_value(&result)?);
+            self.order.push_back(key);
+        }
+        Ok(result)
+    }
+    
+    /// Number of cached results
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    /// Whether the cache holds no results
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    
+    /// Remove all cached results
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+/// A window of multichannel data time-locked to an event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Epoch {
+    pub channels: Vec<String>,
+    pub sampling_rate: f64, // Hz
+    /// Time of the first sample relative to the event in seconds, negative when the epoch starts before it
+    pub tmin: f64,
+    pub data: Vec<Vec<f64>>, // channel x time
+}
+
+impl Epoch {
+    pub fn new(channels: Vec<String>, sampling_rate: f64, tmin: f64, data: Vec<Vec<f64>>) -> Self {
+        Epoch {
+            channels,
+            sampling_rate,
+            tmin,
+            data,
+        }
+    }
+    
+    /// Express each sample as percent change from the channel's mean over a baseline window
+    ///
+    /// The window covers samples at event-relative times in
+    /// `[baseline_start_s, baseline_end_s)`. Channels whose baseline mean is
+    /// zero have no defined percent change and come back as all NaN.
+    pub fn percent_change_from_baseline(&self, baseline_start_s: f64, baseline_end_s: f64) -> Result<Vec<Vec<f64>>, Box<dyn Error>> {
+        if self.sampling_rate <= 0.0 {
+            return Err(format!("Invalid sampling rate: {}", self.sampling_rate).into());
+        }
+        if baseline_start_s >= baseline_end_s {
+            return Err(format!("Empty baseline window [{}, {})", baseline_start_s, baseline_end_s).into());
+        }
+        
+        // First sample at or after a time; the small epsilon absorbs rounding of exact sample times
+        let sample_at = |t: f64| ((t - self.tmin) * self.sampling_rate - 1e-9).ceil().max(0.0) as usize;
+        let start = sample_at(baseline_start_s);
+        let end = sample_at(baseline_end_s);
+        
+        self.data
+            .iter()
+            .zip(&self.channels)
+            .map(|(data, name)| {
+                let baseline = data
+                    .get(start..end.min(data.len()))
+                    .filter(|baseline| !baseline.is_empty())
+                    .ok_or_else(|| format!("Baseline window has no samples for channel {}", name))?;
+                let mean = baseline.iter().sum::<f64>() / baseline.len() as f64;
+                
+                Ok(data
+                    .iter()
+                    .map(|value| if mean == 0.0 { f64::NAN } else { (value - mean) / mean * 100.0 })
+                    .collect())
+            })
+            .collect()
+    }
+}
+
+/// Represents metadata for a brain imaging study
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BrainStudyMetadata {
+    pub subject_id: String,
+    pub age: Option<u8>,
+    pub sex: Option<String>,
+    pub diagnosis: Option<String>,
+    pub study_date: String,
+    pub experiment_type: String,
+    pub institution: String,
+    pub researchers: Vec<String>,
+    pub equipment: HashMap<String, String>,
+    pub notes: Option<String>,
+    pub protocol_id: Option<String>,
+}
+
+impl BrainStudyMetadata {
+    pub fn new(subject_id: &str, experiment_type: &str, institution: &str) -> Self {
+        BrainStudyMetadata {
+            subject_id: subject_id.to_string(),
+            age: None,
+            sex: None,
+            diagnosis: None,
+            study_date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+            experiment_type: experiment_type.to_string(),
+            institution: institution.to_string(),
+            researchers: Vec::new(),
+            equipment: HashMap::new(),
+            notes: None,
+            protocol_id: None,
+        }
+    }
+    
+    pub fn add_researcher(&mut self, name: &str) {
+        self.researchers.push(name.to_string());
+    }
+    
+    pub fn add_equipment(&mut self, name: &str, details: &str) {
+        self.equipment.insert(name.to_string(), details.to_string());
+    }
+    
+    pub fn to_json(&self) -> Result<String, Box<dyn Error>> {
+        let json = serde_json::to_string(self)?;
+        Ok(json)
+    }
+    
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn Error>> {
+        let metadata: BrainStudyMetadata = serde_json::from_str(json)?;
+        Ok(metadata)
+    }
+}
+
+/// Category used by `StudyCohort` for missing demographic fields
+pub const UNKNOWN_CATEGORY: &str = "unknown";
+
+/// A group of study subjects, for demographic breakdowns across datasets
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StudyCohort {
+    pub studies: Vec<BrainStudyMetadata>,
+}
+
+impl StudyCohort {
+    pub fn new(studies: Vec<BrainStudyMetadata>) -> Self {
+        StudyCohort { studies }
+    }
+    
+    /// Subjects per decade of age, keyed like `"30-39"`
+    pub fn age_distribution(&self) -> HashMap<String, usize> {
+        self.count_by(|study| {
+            study.age.map(|age| {
+                let decade = age / 10 * 10;
+                format!("{}-{}", decade, decade as u16 + 9)
+            })
+        })
+    }
+    
+    /// Subjects per recorded sex
+    pub fn sex_distribution(&self) -> HashMap<String, usize> {
+        self.count_by(|study| study.sex.clone())
+    }
+    
+    /// Subjects per diagnosis
+    pub fn diagnosis_counts(&self) -> HashMap<String, usize> {
+        self.count_by(|study| study.diagnosis.clone())
+    }
+    
+    /// Count studies by a category, putting missing values under `UNKNOWN_CATEGORY`
+    fn count_by(&self, category: impl Fn(&BrainStudyMetadata) -> Option<String>) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for study in &self.studies {
+            let key = category(study).unwrap_or_else(|| UNKNOWN_CATEGORY.to_string());
+            *counts.entry(key).or_insert(0) += 1;
+        }
+        counts
+    }
+}
+
+/// Build the provenance transaction recording an uploaded series
+///
+/// The transaction's `data` is the canonical JSON of the CID, format, channel
+/// count, sampling rate and duration, so every client records submissions
+/// in the same shape. The transaction still needs to be signed.
+pub fn submission_transaction(series: &NeuralTimeSeries, cid: &str, sender: &str) -> Transaction {
+    let provenance = serde_json::json!({
+        "cid": cid,
+        "format": series.format,
+        "channels": series.channels.len(),
+        "sampling_rate": series.sampling_rate,
+        "duration_seconds": series.duration_seconds(),
+    });
+    Transaction::new(
+        TransactionType::DataSubmission,
+        sender,
+        &crypto::canonical_json_value(&provenance),
+    )
+}
+
+/// Resample several series onto a shared time grid at `target_rate`
+///
+/// Each series is linearly interpolated onto timestamps spanning the range
+/// covered by every input, so the results can be concatenated or correlated
+/// sample-by-sample. Series without timestamps are assumed to start at 0.
+pub fn align_series(series: &[NeuralTimeSeries], target_rate: f64) -> Result<Vec<NeuralTimeSeries>, Box<dyn Error>> {
+    if target_rate <= 0.0 {
+        return Err(format!("Target rate must be positive, got {}", target_rate).into());
+    }
+    if series.is_empty() {
+        return Ok(Vec::new());
+    }
+    
+    let all_timestamps: Vec<Vec<f64>> = series.iter().map(|s| s.effective_timestamps()).collect();
+    
+    let mut start = f64::NEG_INFINITY;
+    let mut end = f64::INFINITY;
+    for timestamps in &all_timestamps {
+        match (timestamps.first(), timestamps.last()) {
+            (Some(&first), Some(&last)) => {
+                start = start.max(first);
+                end = end.min(last);
+            }
+            _ => return Err("Cannot align a series with no samples".into()),
+        }
+    }
+    if start > end {
+        return Err(format!("Series do not overlap (latest start {} is after earliest end {})", start, end).into());
+    }
+    
+    let num_samples = ((end - start) * target_rate + 1e-9).floor() as usize + 1;
+    let grid: Vec<f64> = (0..num_samples)
+        .map(|i| start + i as f64 / target_rate)
+        .collect();
+    
+    let mut aligned = Vec::with_capacity(series.len());
+    for (source, timestamps) in series.iter().zip(&all_timestamps) {
+        let mut resampled = NeuralTimeSeries::new(source.format, target_rate, &source.units);
+        resampled.metadata = source.metadata.clone();
+        resampled.channel_positions = source.channel_positions.clone();
+        resampled.set_timestamps(grid.clone())?;
+        
+        for (name, data) in source.channels.iter().zip(&source.data) {
+            let values = grid.iter().map(|&t| interpolate(timestamps, data, t)).collect();
+            resampled.add_channel(name, values)?;
+        }
+        aligned.push(resampled);
+    }
+    
+    Ok(aligned)
+}
+
+/// Grand mean of several epochs, e.g. to compute an event-related potential
+///
+/// Every epoch must have the same channels in the same order, the same
+/// sampling rate and start time, and the same number of samples.
+pub fn average_epochs(epochs: &[Epoch]) -> Result<Epoch, Box<dyn Error>> {
+    let first = epochs.first().ok_or("No epochs to average")?;
+    let n_samples = first.data.first().map_or(0, |channel| channel.len());
+    
+    for (i, epoch) in epochs.iter().enumerate() {
+        if epoch.channels != first.channels {
+            return Err(format!("Epoch {} has different channels", i).into());
+        }
+        if epoch.sampling_rate != first.sampling_rate || epoch.tmin != first.tmin {
+            return Err(format!("Epoch {} has a different sampling rate or start time", i).into());
+        }
+        if epoch.data.len() != first.channels.len() || epoch.data.iter().any(|channel| channel.len() != n_samples) {
+            return Err(format!("Epoch {} does not have {} samples per channel", i, n_samples).into());
+        }
+    }
+    
+    let count = epochs.len() as f64;
+    let data = (0..first.channels.len())
+        .map(|c| {
+            (0..n_samples)
+                .map(|t| epochs.iter().map(|epoch| epoch.data[c][t]).sum::<f64>() / count)
+                .collect()
+        })
+        .collect();
+    
+    Ok(Epoch::new(first.channels.clone(), first.sampling_rate, first.tmin, data))
+}
+
+/// Common spatial patterns for two-class data such as motor-imagery BCI trials
+///
+/// Solves the generalized eigenvalue problem `C_a w = λ (C_a + C_b) w` on the
+/// class-averaged, trace-normalized channel covariances. Returns
+/// `2 * n_components` spatial filters (one weight per channel, in the channel
+/// order of the first trial of `class_a`): the first `n_components` maximize
+/// variance for class A relative to class B, the last `n_components` the reverse.
+/// Every trial must contain the same set of channels.
+pub fn common_spatial_patterns(
+    class_a: &[NeuralTimeSeries],
+    class_b: &[NeuralTimeSeries],
+    n_components: usize,
+) -> Result<Vec<Vec<f64>>, Box<dyn Error>> {
+    let first = match (class_a.first(), class_b.first()) {
+        (Some(first), Some(_)) => first,
+        _ => return Err("CSP requires at least one trial per class".into()),
+    };
+    let channels = &first.channels;
+    let n_channels = channels.len();
+    if n_components == 0 || 2 * n_components > n_channels {
+        return Err(format!(
+            "Cannot extract {} filters per class from {} channels",
+            n_components, n_channels
+        ).into());
+    }
+    
+    let class_covariance = |trials: &[NeuralTimeSeries]| -> Result<Vec<Vec<f64>>, Box<dyn Error>> {
+        let mut average = vec![vec![0.0; n_channels]; n_channels];
+        for trial in trials {
+            let covariance = normalized_covariance(trial, channels)?;
+            for (row, trial_row) in average.iter_mut().zip(&covariance) {
+                for (value, c) in row.iter_mut().zip(trial_row) {
+                    *value += c / trials.len() as f64;
+                }
+            }
+        }
+        Ok(average)
+    };
+    let covariance_a = class_covariance(class_a)?;
+    let covariance_b = class_covariance(class_b)?;
+    
+    // Reduce C_a w = λ (C_a + C_b) w to a standard symmetric problem with the
+    // Cholesky factor L of the composite: M = L⁻¹ C_a L⁻ᵀ, w = L⁻ᵀ u
+    let covariance_a = to_matrix(&covariance_a);
+    let composite = &covariance_a + to_matrix(&covariance_b);
+    let l = match composite.cholesky() {
+        Some(cholesky) => cholesky.unpack(),
+        None => return Err("Composite covariance is rank deficient".into()),
+    };
+    if l.diagonal().iter().any(|&d| d * d <= 1e-12) {
+        return Err("Composite covariance is rank deficient".into());
+    }
+    let half = l.solve_lower_triangular_unchecked(&covariance_a);
+    let reduced = l.solve_lower_triangular_unchecked(&half.transpose());
+    let eigen = reduced.symmetric_eigen();
+    let filters = l.transpose().solve_upper_triangular_unchecked(&eigen.eigenvectors);
+    
+    let mut order: Vec<usize> = (0..n_channels).collect();
+    order.sort_by(|&a, &b| eigen.eigenvalues[b].total_cmp(&eigen.eigenvalues[a]));
+    
+    let selected = order[..n_components].iter().chain(&order[n_channels - n_components..]);
+    Ok(selected
+        .map(|&k| filters.column(k).iter().copied().collect())
+        .collect())
+}
+
+/// Trace-normalized covariance of `trial`'s channels, taken in the order of `channels`
+fn normalized_covariance(trial: &NeuralTimeSeries, channels: &[String]) -> Result<Vec<Vec<f64>>, Box<dyn Error>> {
+    if trial.channels.len() != channels.len() {
+        return Err("All CSP trials must have the same channels".into());
+    }
+    let centered: Vec<Vec<f64>> = channels
+        .iter()
+        .map(|name| {
+            let data = trial
+                .get_channel_data(name)
+                .ok_or_else(|| format!("Channel {} missing from a CSP trial", name))?;
+            let mean = data.iter().sum::<f64>() / data.len().max(1) as f64;
+            Ok(data.iter().map(|v| v - mean).collect())
+        })
+        .collect::<Result<_, Box<dyn Error>>>()?;
+    
+    let n = channels.len();
+    let mut covariance = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in i..n {
+            let c = dot(&centered[i], &centered[j]);
+            covariance[i][j] = c;
+            covariance[j][i] = c;
+        }
+    }
+    
+    let trace: f64 = (0..n).map(|i| covariance[i][i]).sum();
+    if trace <= 0.0 {
+        return Err("CSP trial has no variance".into());
+    }
+    covariance.iter_mut().flatten().for_each(|value| *value /= trace);
+    Ok(covariance)
+}
+
+/// Shannon entropy in bits of a distribution given by non-negative weights
+///
+/// Weights are normalized to sum to 1; an all-zero distribution has entropy 0.
+fn entropy_bits(weights: impl Iterator<Item = f64> + Clone) -> f64 {
+    let total: f64 = weights.clone().sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+    
+    weights
+        .filter(|&w| w > 0.0)
+        .map(|w| {
+            let p = w / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Deserialize channel x time samples, reading `null` (how JSON stores NaN) back as NaN
+fn deserialize_samples<'de, D>(deserializer: D) -> Result<Vec<Vec<f64>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let samples: Vec<Vec<Option<f64>>> = Deserialize::deserialize(deserializer)?;
+    Ok(samples
+        .into_iter()
+        .map(|channel| channel.into_iter().map(|v| v.unwrap_or(f64::NAN)).collect())
+        .collect())
+}
+
+/// Median of the non-NaN values, or `None` if there are none
+fn median(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let mut sorted: Vec<f64> = values.filter(|v| !v.is_nan()).collect();
+    if sorted.is_empty() {
+        return None;
+    }
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    
+    let mid = sorted.len() / 2;
+    if sorted.len() & 1 == 0 {
+        Some((sorted[mid - 1] + sorted[mid]) / 2.0)
+    } else {
+        Some(sorted[mid])
+    }
+}
+
+/// Pearson correlation of two equal-length slices, or `None` if either is constant
+fn pearson(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    let n = xs.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let mean_y = ys.iter().sum::<f64>() / n;
+    
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (&x, &y) in xs.iter().zip(ys) {
+        let dx = x - mean_x;
+        let dy = y - mean_y;
+        cov += dx * dy;
+        var_x += dx * dx;
+        var_y += dy * dy;
+    }
+    
+    if var_x <= 0.0 || var_y <= 0.0 {
+        return None;
+    }
+    Some(cov / (var_x * var_y).sqrt())
+}
+
+/// Estimated bytes added by the values of a JSON number array, including separators
+fn estimated_array_bytes(values: &[f64]) -> usize {
+    if values.is_empty() {
+        return 0;
+    }
+    
+    let step = values.len().div_ceil(SIZE_ESTIMATE_SAMPLES);
+    let (measured, width) = values
+        .iter()
+        .step_by(step)
+        .map(|value| serde_json::to_string(value).map(|s| s.len()).unwrap_or(0))
+        .fold((0, 0), |(count, total), len| (count + 1, total + len));
+    
+    let separators = values.len() - 1;
+    (width as f64 / measured as f64 * values.len() as f64).round() as usize + separators
+}
+
+/// Idealized 10-20 electrode positions on a unit sphere
+///
+/// Axes follow the RAS convention: x points to the right preauricular point,
+/// y to the nasion and z to the vertex (Cz). The older labels T3/T4/T5/T6 are
+/// included as aliases of T7/T8/P7/P8.
+const STANDARD_1020: &[(&str, (f64, f64, f64))] = &[
+    ("Fp1", (-0.309017, 0.951057, 0.0)),
+    ("Fpz", (0.0, 1.0, 0.0)),
+    ("Fp2", (0.309017, 0.951057, 0.0)),
+    ("F7", (-0.809017, 0.587785, 0.0)),
+    ("F3", (-0.545, 0.673, 0.5)),
+    ("Fz", (0.0, FRAC_1_SQRT_2, FRAC_1_SQRT_2)),
+    ("F4", (0.545, 0.673, 0.5)),
+    ("F8", (0.809017, 0.587785, 0.0)),
+    ("T7", (-1.0, 0.0, 0.0)),
+    ("C3", (-FRAC_1_SQRT_2, 0.0, FRAC_1_SQRT_2)),
+    ("Cz", (0.0, 0.0, 1.0)),
+    ("C4", (FRAC_1_SQRT_2, 0.0, FRAC_1_SQRT_2)),
+    ("T8", (1.0, 0.0, 0.0)),
+    ("P7", (-0.809017, -0.587785, 0.0)),
+    ("P3", (-0.545, -0.673, 0.5)),
+    ("Pz", (0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2)),
+    ("P4", (0.545, -0.673, 0.5)),
+    ("P8", (0.809017, -0.587785, 0.0)),
+    ("O1", (-0.309017, -0.951057, 0.0)),
+    ("Oz", (0.0, -1.0, 0.0)),
+    ("O2", (0.309017, -0.951057, 0.0)),
+    ("T3", (-1.0, 0.0, 0.0)),
+    ("T4", (1.0, 0.0, 0.0)),
+    ("T5", (-0.809017, -0.587785, 0.0)),
+    ("T6", (0.809017, -0.587785, 0.0)),
+];
+
+/// Canonical coordinates for common 10-20 electrode labels
+pub fn standard_1020_positions() -> HashMap<String, (f64, f64, f64)> {
+    STANDARD_1020
+        .iter()
+        .map(|&(label, position)| (label.to_string(), position))
+        .collect()
+}
+
+/// Azimuthal equidistant projection of a 3D electrode position onto the plane
+///
+/// The vertex (+z) maps to the origin and the equator to radius 1.
+fn project_azimuthal((x, y, z): (f64, f64, f64)) -> (f64, f64) {
+    let norm = (x * x + y * y + z * z).sqrt();
+    if norm == 0.0 {
+        return (0.0, 0.0);
+    }
+    let radius = (z / norm).clamp(-1.0, 1.0).acos() / std::f64::consts::FRAC_PI_2;
+    let azimuth = y.atan2(x);
+    (radius * azimuth.cos(), radius * azimuth.sin())
+}
+
+/// Inverse-square-distance weighted value at `point`; exact at an electrode
+fn inverse_distance_weighted(electrodes: &[((f64, f64), f64)], point: (f64, f64)) -> f64 {
+    let mut weighted = 0.0;
+    let mut total_weight = 0.0;
+    for &((x, y), value) in electrodes {
+        let distance_sq = (x - point.0).powi(2) + (y - point.1).powi(2);
+        if distance_sq < 1e-18 {
+            return value;
+        }
+        weighted += value / distance_sq;
+        total_weight += 1.0 / distance_sq;
+    }
+    weighted / total_weight
+}
+
+/// Linearly interpolate `data` sampled at `timestamps` at time `t`
+fn interpolate(timestamps: &[f64], data: &[f64], t: f64) -> f64 {
+    let n = timestamps.len().min(data.len());
+    if n == 0 {
+        return 0.0;
+    }
+    
+    let upper = timestamps[..n].partition_point(|&ts| ts < t);
+    if upper == 0 {
+        return data[0];
+    }
+    if upper >= n {
+        return data[n - 1];
+    }
+    
+    let (t0, t1) = (timestamps[upper - 1], timestamps[upper]);
+    let (v0, v1) = (data[upper - 1], data[upper]);
+    if t1 == t0 {
+        return v0;
+    }
+    v0 + (v1 - v0) * (t - t0) / (t1 - t0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    
+    /// `n` reproducible samples of uniform noise in `[-1, 1)`
+    fn seeded_noise(seed: u64, n: usize) -> Vec<f64> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..n).map(|_| rng.gen_range(-1.0..1.0)).collect()
+    }
+
+    #[test]
+    fn test_create_neural_time_series() {
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 256.0, "microvolts");
+        
+        // Add timestamps
+        ts.generate_timestamps(0.0, 5);
+        assert_eq!(ts.timestamps, vec![0.0, 0.00390625, 0.0078125, 0.01171875, 0.015625]);
+        
+        // Add a channel
+        let data = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        ts.add_channel("Fz", data.clone()).unwrap();
+        
+        assert_eq!(ts.channels.len(), 1);
+        assert_eq!(ts.data.len(), 1);
+        assert_eq!(ts.get_channel_data("Fz").unwrap(), &data);
+        
+        // Add metadata
+        ts.add_metadata("subject", "S001");
+        assert_eq!(ts.metadata.get("subject").unwrap(), "S001");
+    }
+    
+    #[test]
+    fn test_average_epochs() {
+        let epoch = |values: Vec<f64>| Epoch::new(vec!["Cz".to_string()], 250.0, -0.1, vec![values]);
+        let epochs = vec![
+            epoch(vec![1.0, 2.0, 3.0]),
+            epoch(vec![3.0, 2.0, 6.0]),
+            epoch(vec![2.0, 5.0, 0.0]),
+        ];
+        
+        let average = average_epochs(&epochs).unwrap();
+        assert_eq!(average.channels, vec!["Cz".to_string()]);
+        assert_eq!(average.tmin, -0.1);
+        assert_eq!(average.data, vec![vec![2.0, 3.0, 3.0]]);
+        
+        assert!(average_epochs(&[]).is_err());
+        assert!(average_epochs(&[epoch(vec![1.0, 2.0, 3.0]), epoch(vec![1.0, 2.0])]).is_err());
+        let renamed = Epoch::new(vec!["Pz".to_string()], 250.0, -0.1, vec![vec![1.0, 2.0, 3.0]]);
+        assert!(average_epochs(&[epoch(vec![1.0, 2.0, 3.0]), renamed]).is_err());
+    }
+    
+    #[test]
+    fn test_percent_change_from_baseline() {
+        // 100 Hz, 200 ms before to 300 ms after the event
+        let evoked: Vec<f64> = (0..50).map(|i| if i < 20 { 4.0 } else { 5.0 }).collect();
+        let mut shifted_baseline = vec![2.0; 50];
+        shifted_baseline[10] = 6.0;
+        let epoch = Epoch::new(
+            vec!["Cz".to_string(), "Pz".to_string(), "Oz".to_string()],
+            100.0,
+            -0.2,
+            vec![evoked, shifted_baseline, vec![0.0; 50]],
+        );
+        
+        let change = epoch.percent_change_from_baseline(-0.2, 0.0).unwrap();
+        assert_eq!(change.len(), 3);
+        assert!(change[0][..20].iter().all(|&v| v.abs() < 1e-9));
+        assert!(change[0][20..].iter().all(|&v| (v - 25.0).abs() < 1e-9));
+        
+        // Pz's baseline mean is (19 * 2 + 6) / 20 = 2.2
+        assert!((change[1][0] - (2.0 - 2.2) / 2.2 * 100.0).abs() < 1e-9);
+        
+        // Zero baseline mean
+        assert!(change[2].iter().all(|v| v.is_nan()));
+        
+        // The window excludes its end: [-0.2, -0.1) is samples 0..10, before the spike at 10
+        let early = epoch.percent_change_from_baseline(-0.2, -0.1).unwrap();
+        assert!(early[1][0].abs() < 1e-9);
+        
+        assert!(epoch.percent_change_from_baseline(0.0, -0.1).is_err());
+        assert!(epoch.percent_change_from_baseline(1.0, 2.0).is_err());
+    }
+    
+    #[test]
+    fn test_study_cohort() {
+        let subjects = [
+            (Some(24), Some("F"), Some("healthy")),
+            (Some(29), Some("M"), Some("epilepsy")),
+            (Some(35), Some("F"), Some("epilepsy")),
+            (Some(30), None, None),
+            (None, Some("M"), Some("healthy")),
+        ];
+        let studies = subjects
+            .iter()
+            .enumerate()
+            .map(|(i, &(age, sex, diagnosis))| {
+                let mut study = BrainStudyMetadata::new(&format!("sub-{:02}", i), "resting-state", "NeuraDeSci Lab");
+                study.age = age;
+                study.sex = sex.map(str::to_string);
+                study.diagnosis = diagnosis.map(str::to_string);
+                study
+            })
+            .collect();
+        let cohort = StudyCohort::new(studies);
+        
+        let ages = cohort.age_distribution();
+        assert_eq!(ages.len(), 3);
+        assert_eq!(ages["20-29"], 2);
+        assert_eq!(ages["30-39"], 2);
+        assert_eq!(ages[UNKNOWN_CATEGORY], 1);
+        
+        let sexes = cohort.sex_distribution();
+        assert_eq!((sexes["F"], sexes["M"], sexes[UNKNOWN_CATEGORY]), (2, 2, 1));
+        
+        let diagnoses = cohort.diagnosis_counts();
+        assert_eq!((diagnoses["healthy"], diagnoses["epilepsy"], diagnoses[UNKNOWN_CATEGORY]), (2, 2, 1));
+        
+        assert!(StudyCohort::default().age_distribution().is_empty());
+    }
+    
+    #[test]
+    fn test_validate_for_format() {
+        let mut eeg = NeuralTimeSeries::new(NeuralDataFormat::EEG, 250.0, "microvolts");
+        eeg.add_channel("Cz", vec![0.0; 10]).unwrap();
+        assert_eq!(eeg.validate_for_format(), vec![ValidationWarning::LowChannelCount {
+            format: NeuralDataFormat::EEG,
+            channels: 1,
+            minimum: 8,
+        }]);
+        
+        // A custom minimum accepts the single-channel recording
+        let relaxed = FormatExpectations { min_channels: 1 };
+        assert!(eeg.validate_with_expectations(&relaxed).is_empty());
+        
+        let mut fmri = NeuralTimeSeries::new(NeuralDataFormat::FMRI, 0.5, "BOLD");
+        fmri.add_channel("global_signal", vec![0.0; 10]).unwrap();
+        assert_eq!(fmri.validate_for_format(), vec![ValidationWarning::FlatFmriSeries]);
+        
+        fmri.add_channel("voxel_1_1_1", vec![0.0; 10]).unwrap();
+        assert!(fmri.validate_for_format().is_empty());
+    }
+    
+    #[test]
+    fn test_bids_modality_round_trip() {
+        let formats = [
+            NeuralDataFormat::EEG,
+            NeuralDataFormat::FMRI,
+            NeuralDataFormat::MEG,
+            NeuralDataFormat::PET,
+            NeuralDataFormat::MRI,
+            NeuralDataFormat::CT,
+            NeuralDataFormat::SPECT,
+            NeuralDataFormat::ECOG,
+            NeuralDataFormat::SingleUnitRecording,
+            NeuralDataFormat::Custom,
+        ];
+        for format in formats {
+            assert_eq!(NeuralDataFormat::from_bids_modality(format.as_bids_modality()), Some(format));
+            assert!(format.content_type().contains('/'));
+        }
+        
+        assert_eq!(NeuralDataFormat::FMRI.as_bids_modality(), "bold");
+        assert_eq!(NeuralDataFormat::from_bids_modality("EEG"), Some(NeuralDataFormat::EEG));
+        assert_eq!(NeuralDataFormat::from_bids_modality("dwi"), None);
+        assert_eq!(NeuralDataFormat::from_bids_modality(""), None);
+    }
+    
+    #[test]
+    fn test_topo_interpolate() {
+        let positions = standard_1020_positions();
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 250.0, "microvolts");
+        for (name, value) in [("Fz", 10.0), ("C3", -5.0), ("C4", 3.0)] {
+            ts.add_channel(name, vec![0.0, value]).unwrap();
+            ts.set_position(name, positions[name]).unwrap();
+        }
+        
+        // Fz projects to (0, 0.5), C3 to (-0.5, 0) and C4 to (0.5, 0), so a
+        // 41-cell grid has a cell on each electrode
+        let map = ts.topo_interpolate(1, 41).unwrap();
+        assert_eq!(map.len(), 41);
+        assert!(map.iter().all(|row| row.len() == 41));
+        assert!((map[0][20] - 10.0).abs() < 1e-9);
+        assert!((map[20][0] + 5.0).abs() < 1e-9);
+        assert!((map[20][40] - 3.0).abs() < 1e-9);
+        
+        // One cell away from C3 the value is dominated by C3
+        assert!((map[20][1] + 5.0).abs() < 0.1, "value {}", map[20][1]);
+        let all: Vec<f64> = map.iter().flatten().copied().collect();
+        assert!(all.iter().all(|&v| (-5.0..=10.0).contains(&v)));
+        
+        assert!(ts.topo_interpolate(2, 41).is_err());
+        assert!(ts.topo_interpolate(1, 0).is_err());
+        ts.add_channel("Pz", vec![0.0, 1.0]).unwrap();
+        assert!(ts.topo_interpolate(1, 41).is_err());
+    }
+    
+    #[test]
+    fn test_channel_positions() {
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 256.0, "microvolts");
+        ts.add_channel("C3", vec![0.0; 4]).unwrap();
+        
+        assert_eq!(ts.position("C3"), None);
+        ts.set_position("C3", (-0.7, 0.0, 0.7)).unwrap();
+        assert_eq!(ts.position("C3"), Some((-0.7, 0.0, 0.7)));
+        assert!(ts.set_position("Cz", (0.0, 0.0, 1.0)).is_err());
+        
+        let standard = standard_1020_positions();
+        let c3 = standard["C3"];
+        assert_ne!(c3, (0.0, 0.0, 0.0));
+        assert!(c3.0 < 0.0, "C3 lies over the left hemisphere");
+        assert_eq!(standard["T3"], standard["T7"]);
+        
+        let restored = NeuralTimeSeries::from_json(&ts.to_json().unwrap()).unwrap();
+        assert_eq!(restored.position("C3"), Some((-0.7, 0.0, 0.7)));
+    }
+    
+    #[test]
+    fn test_apply_gain() {
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 256.0, "microvolts");
+        ts.add_channel("Fz", vec![1.0, 2.0, 3.0]).unwrap();
+        ts.add_channel("Cz", vec![1.0, 2.0, 3.0]).unwrap();
+        
+        let gains = HashMap::from([("Fz".to_string(), 2.0)]);
+        let offsets = HashMap::from([("Fz".to_string(), 1.0)]);
+        ts.apply_gain(&gains, &offsets);
+        
+        assert_eq!(ts.get_channel_data("Fz").unwrap(), &vec![3.0, 5.0, 7.0]);
+        assert_eq!(ts.get_channel_data("Cz").unwrap(), &vec![1.0, 2.0, 3.0]);
+    }
+    
+    #[test]
+    fn test_calculate_statistics() {
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 256.0, "microvolts");
+        
+        // Add a channel
+        let data = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        ts.add_channel("Fz", data).unwrap();
+        
+        let stats = ts.calculate_channel_stats("Fz").unwrap();
+        
+        assert_eq!(stats.min, 1.0);
+        assert_eq!(stats.max, 5.0);
+        assert_eq!(stats.mean, 3.0);
+        assert!(stats.std_dev - 1.4142135 < 0.0001);
+    }
+
+    #[test]
+    fn test_duration_from_timestamps() {
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 256.0, "microvolts");
+        ts.set_timestamps(vec![1.0, 1.5, 2.0, 3.5]).unwrap();
+        
+        assert_eq!(ts.sample_count(), 4);
+        assert_eq!(ts.duration_seconds(), 2.5);
+    }
+    
+    #[test]
+    fn test_duration_without_timestamps() {
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 256.0, "microvolts");
+        assert_eq!(ts.sample_count(), 0);
+        assert_eq!(ts.duration_seconds(), 0.0);
+        
+        ts.add_channel("Fz", vec![0.0; 512]).unwrap();
+        assert_eq!(ts.sample_count(), 512);
+        assert_eq!(ts.duration_seconds(), 2.0);
+    }
+    
+    #[test]
+    fn test_submission_transaction() {
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 250.0, "microvolts");
+        ts.add_channel("Fz", vec![0.0; 500]).unwrap();
+        ts.add_channel("Cz", vec![0.0; 500]).unwrap();
+        
+        let tx = submission_transaction(&ts, "QmRecording", "researcher_001");
+        assert_eq!(tx.transaction_type, TransactionType::DataSubmission);
+        assert_eq!(tx.sender, "researcher_001");
+        
+        let data: serde_json::Value = serde_json::from_str(&tx.data).unwrap();
+        assert_eq!(data["cid"], "QmRecording");
+        assert_eq!(data["format"], "EEG");
+        assert_eq!(data["channels"], 2);
+        assert_eq!(data["sampling_rate"], 250.0);
+        assert_eq!(data["duration_seconds"], 2.0);
+        assert_eq!(tx.data, crypto::canonical_json_value(&data));
+    }
+    
+    #[test]
+    fn test_align_series() {
+        let mut slow = NeuralTimeSeries::new(NeuralDataFormat::EEG, 128.0, "microvolts");
+        slow.generate_timestamps(0.0, 256);
+        slow.add_channel("Fz", (0..256).map(|i| i as f64).collect()).unwrap();
+        
+        let mut fast = NeuralTimeSeries::new(NeuralDataFormat::EEG, 256.0, "microvolts");
+        fast.generate_timestamps(0.5, 512);
+        fast.add_channel("Cz", (0..512).map(|i| i as f64).collect()).unwrap();
+        
+        let aligned = align_series(&[slow, fast], 100.0).unwrap();
+        assert_eq!(aligned.len(), 2);
+        assert_eq!(aligned[0].sample_count(), aligned[1].sample_count());
+        assert_eq!(aligned[0].timestamps, aligned[1].timestamps);
+        assert_eq!(aligned[0].sampling_rate, 100.0);
+        
+        // Overlap is [0.5, 1.9921875], so the grid starts at 0.5 with 150 samples
+        assert_eq!(aligned[0].sample_count(), 150);
+        assert_eq!(aligned[0].timestamps[0], 0.5);
+        // The 128 Hz ramp reaches sample 64 at t = 0.5
+        assert!((aligned[0].data[0][0] - 64.0).abs() < 1e-9);
+    }
+    
+    #[test]
+    fn test_align_series_without_overlap() {
+        let mut first = NeuralTimeSeries::new(NeuralDataFormat::EEG, 100.0, "microvolts");
+        first.generate_timestamps(0.0, 100);
+        first.add_channel("Fz", vec![0.0; 100]).unwrap();
+        
+        let mut second = NeuralTimeSeries::new(NeuralDataFormat::EEG, 100.0, "microvolts");
+        second.generate_timestamps(5.0, 100);
+        second.add_channel("Fz", vec![0.0; 100]).unwrap();
+        
+        assert!(align_series(&[first, second], 50.0).is_err());
+    }
+    
+    #[test]
+    fn test_reject_outliers_iqr() {
+        let mut data: Vec<f64> = (0..100).map(|i| (i % 10) as f64).collect();
+        data[17] = 250.0;
+        data[42] = -180.0;
+        data[88] = 31.0;
+        let original = data.clone();
+        
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 250.0, "microvolts");
+        ts.add_channel("Cz", data).unwrap();
+        assert_eq!(ts.percentile("Cz", 50.0), Some(4.5));
+        assert_eq!(ts.percentile("Cz", 101.0), None);
+        
+        assert_eq!(ts.reject_outliers_iqr("Cz", 1.5), 3);
+        let cleaned = ts.get_channel_data("Cz").unwrap();
+        for (i, (&value, &before)) in cleaned.iter().zip(&original).enumerate() {
+            if [17, 42, 88].contains(&i) {
+                assert!(value.is_nan());
+            } else {
+                assert_eq!(value, before);
+            }
+        }
+        
+        // Rejected samples survive a JSON round trip as NaN
+        let restored = NeuralTimeSeries::from_json(&ts.to_json().unwrap()).unwrap();
+        let restored = restored.get_channel_data("Cz").unwrap();
+        assert!(restored.iter().zip(cleaned).all(|(a, b)| a == b || (a.is_nan() && b.is_nan())));
+        
+        // Already-rejected samples are not counted again
+        assert_eq!(ts.reject_outliers_iqr("Cz", 1.5), 0);
+        assert_eq!(ts.reject_outliers_iqr("Oz", 1.5), 0);
+    }
+    
+    #[test]
+    fn test_phase_locking_value() {
+        let rate = 250.0;
+        let alpha = |i: usize, lag: f64| (2.0 * std::f64::consts::PI * 10.0 * i as f64 / rate + lag).sin();
+        let reference: Vec<f64> = (0..5000).map(|i| alpha(i, 0.0)).collect();
+        // Same rhythm at a fixed lag and a different amplitude, plus out-of-band noise
+        let locked: Vec<f64> = (0..5000)
+            .map(|i| 3.0 * alpha(i, 1.2) + (2.0 * std::f64::consts::PI * 40.0 * i as f64 / rate).sin())
+            .collect();
+        let noise = seeded_noise(7, 5000);
+        
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, rate, "microvolts");
+        ts.add_channel("O1", reference).unwrap();
+        ts.add_channel("O2", locked).unwrap();
+        ts.add_channel("Fz", noise).unwrap();
+        
+        let high = ts.phase_locking_value("O1", "O2", (8.0, 12.0)).unwrap();
+        let low = ts.phase_locking_value("O1", "Fz", (8.0, 12.0)).unwrap();
+        assert!(high > 0.99, "locked PLV was {}", high);
+        assert!(low < 0.3, "unrelated PLV was {}", low);
+        
+        assert!(ts.phase_locking_value("O1", "O2", (12.0, 8.0)).is_err());
+        assert!(ts.phase_locking_value("O1", "Oz", (8.0, 12.0)).is_err());
+    }
+    
+    #[test]
+    fn test_robust_zscore() {
+        let mut data: Vec<f64> = (0..50).map(|i| (i % 5) as f64).collect();
+        data[10] = 1000.0;
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 250.0, "microvolts");
+        ts.add_channel("Cz", data.clone()).unwrap();
+        ts.add_channel("flat", vec![2.0; 50]).unwrap();
+        
+        let robust = ts.robust_zscore("Cz").unwrap();
+        let stats = ts.calculate_channel_stats("Cz").unwrap();
+        let ordinary: Vec<f64> = data.iter().map(|v| (v - stats.mean) / stats.std_dev).collect();
+        
+        // The outlier inflates the standard deviation and squashes the spread of normal samples
+        assert!(ordinary[4] - ordinary[0] < 0.1);
+        assert!((robust[4] - 2.0 / 1.4826).abs() < 1e-9);
+        assert!(robust[4] - robust[0] > 2.0);
+        // ...and caps the outlier's own score, which the robust version leaves extreme
+        assert!(ordinary[10] < 8.0);
+        assert!(robust[10] > 100.0);
+        
+        assert_eq!(ts.robust_zscore("flat").unwrap(), vec![0.0; 50]);
+        assert!(ts.robust_zscore("Oz").is_err());
+    }
+    
+    #[test]
+    fn test_to_mne_json() {
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 512.0, "microvolts");
+        ts.add_channel("Fz", vec![1.0, 2.0, 3.0]).unwrap();
+        ts.add_channel("Cz", vec![4.0, 5.0, 6.0]).unwrap();
+        
+        let mne: serde_json::Value = serde_json::from_str(&ts.to_mne_json().unwrap()).unwrap();
+        assert_eq!(mne["info"]["sfreq"], 512.0);
+        assert_eq!(mne["info"]["ch_names"], serde_json::json!(["Fz", "Cz"]));
+        assert_eq!(mne["info"]["ch_types"], serde_json::json!(["eeg", "eeg"]));
+        assert_eq!(mne["data"][1], serde_json::json!([4.0, 5.0, 6.0]));
+        assert_eq!(mne["units"], "microvolts");
+        
+        assert_eq!(NeuralDataFormat::MEG.mne_channel_type(), "mag");
+        assert_eq!(NeuralDataFormat::FMRI.mne_channel_type(), "misc");
+    }
+    
+    #[test]
+    fn test_rolling_correlation() {
+        let a: Vec<f64> = (0..400).map(|i| (i as f64 * 0.3).sin()).collect();
+        // Follows `a` for the first half, then mirrors it
+        let b: Vec<f64> = a
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| if i < 200 { 2.0 * v + 0.5 } else { -v })
+            .collect();
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 100.0, "microvolts");
+        ts.add_channel("C3", a).unwrap();
+        ts.add_channel("C4", b).unwrap();
+        
+        let rolling = ts.rolling_correlation("C3", "C4", 50, 25).unwrap();
+        assert_eq!(rolling.len(), 15);
+        assert!(rolling[..7].iter().all(|&r| (r - 1.0).abs() < 1e-9));
+        assert!(rolling[8..].iter().all(|&r| (r + 1.0).abs() < 1e-9));
+        // The window straddling the switch is in between
+        assert!(rolling[7].abs() < 0.99);
+        
+        assert!(ts.rolling_correlation("C3", "C4", 1, 25).is_err());
+        assert!(ts.rolling_correlation("C3", "C4", 401, 25).is_err());
+        assert!(ts.rolling_correlation("C3", "C4", 50, 0).is_err());
+        assert!(ts.rolling_correlation("C3", "Oz", 50, 25).is_err());
+    }
+    
+    #[test]
+    fn test_autocorrelation() {
+        // Period of 25 samples
+        let data: Vec<f64> = (0..500)
+            .map(|i| (2.0 * std::f64::consts::PI * i as f64 / 25.0).sin() + 3.0)
+            .collect();
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 250.0, "microvolts");
+        ts.add_channel("Cz", data.clone()).unwrap();
+        ts.add_channel("flat", vec![1.0; 500]).unwrap();
+        
+        let acf = ts.autocorrelation("Cz", 40).unwrap();
+        assert_eq!(acf.len(), 41);
+        assert!((acf[0] - 1.0).abs() < 1e-12);
+        assert!(acf[25] > acf[24] && acf[25] > acf[26]);
+        assert!(acf[12] < -0.9);
+        
+        // Matches the direct biased estimate
+        let centered: Vec<f64> = data.iter().map(|v| v - 3.0).collect();
+        let direct = |lag: usize| (0..500 - lag).map(|i| centered[i] * centered[i + lag]).sum::<f64>();
+        assert!((acf[25] - direct(25) / direct(0)).abs() < 1e-9);
+        
+        assert!(ts.autocorrelation("Cz", 500).is_err());
+        assert!(ts.autocorrelation("flat", 10).is_err());
+        assert!(ts.autocorrelation("Oz", 10).is_err());
+    }
+    
+    #[test]
+    fn test_pca_explained_variance() {
+        let signal: Vec<f64> = (0..500).map(|i| (i as f64 * 0.05).sin() * 10.0).collect();
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 250.0, "microvolts");
+        ts.add_channel("C3", signal.clone()).unwrap();
+        ts.add_channel("C4", signal.iter().map(|v| 2.0 * v + 1.0).collect()).unwrap();
+        ts.add_channel("Cz", (0..500).map(|i| ((i * 7 % 13) as f64 - 6.0) * 0.01).collect()).unwrap();
+        
+        let pca = ts.pca(2).unwrap();
+        assert_eq!(pca.components.len(), 2);
+        assert_eq!(pca.projections[0].len(), 500);
+        
+        // C3 and C4 are perfectly correlated, so one component carries almost everything
+        assert!(pca.explained_variance_ratio[0] > 0.999, "ratio {:?}", pca.explained_variance_ratio);
+        assert!(pca.explained_variance_ratio[0] >= pca.explained_variance_ratio[1]);
+        let direction = &pca.components[0];
+        assert!((direction[1] / direction[0] - 2.0).abs() < 1e-6);
+        
+        let all = ts.pca(3).unwrap();
+        assert!((all.explained_variance_ratio.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        
+        assert!(ts.pca(0).is_err());
+        assert!(ts.pca(4).is_err());
+    }
+    
+    #[test]
+    fn test_ica_separates_sources() {
+        let n = 2000;
+        let source_a: Vec<f64> = (0..n).map(|i| (i as f64 * 0.05).sin()).collect();
+        let source_b: Vec<f64> = (0..n).map(|i| ((i % 97) as f64 / 48.5) - 1.0).collect();
+        
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 256.0, "microvolts");
+        ts.add_channel("Fp1", (0..n).map(|i| source_a[i] + 0.6 * source_b[i] + 3.0).collect()).unwrap();
+        ts.add_channel("Fp2", (0..n).map(|i| 0.4 * source_a[i] + source_b[i] - 1.0).collect()).unwrap();
+        
+        let result = ts.ica(2).unwrap();
+        assert_eq!(result.components.len(), 2);
+        assert_eq!(result.mixing.len(), 2);
+        
+        let correlation = |x: &[f64], y: &[f64]| {
+            let mx = x.iter().sum::<f64>() / x.len() as f64;
+            let my = y.iter().sum::<f64>() / y.len() as f64;
+            let cov: f64 = x.iter().zip(y).map(|(a, b)| (a - mx) * (b - my)).sum();
+            let vx: f64 = x.iter().map(|a| (a - mx).powi(2)).sum();
+            let vy: f64 = y.iter().map(|b| (b - my).powi(2)).sum();
+            cov / (vx * vy).sqrt()
+        };
+        
+        // Each source matches one component up to sign and scale
+        for source in [&source_a, &source_b] {
+            let best = result.components
+                .iter()
+                .map(|component| correlation(source, component).abs())
+                .fold(0.0, f64::max);
+            assert!(best > 0.99, "best correlation {}", best);
+        }
+        
+        // Keeping every component reproduces the input
+        let reconstructed = result.reconstruct_without(&[]);
+        for (original, restored) in ts.data.iter().zip(&reconstructed) {
+            for (a, b) in original.iter().zip(restored) {
+                assert!((a - b).abs() < 1e-6);
+            }
+        }
+        
+        // Removing the component matching source B leaves only source A
+        let b_index = if correlation(&source_b, &result.components[0]).abs() > 0.9 { 0 } else { 1 };
+        let cleaned = result.reconstruct_without(&[b_index]);
+        assert!(correlation(&cleaned[0], &source_a).abs() > 0.99);
+        assert!(correlation(&cleaned[1], &source_a).abs() > 0.99);
+    }
+    
+    #[test]
+    fn test_split_fixed() {
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 10.0, "microvolts");
+        ts.generate_timestamps(5.0, 10);
+        ts.add_channel("Fz", (0..10).map(|i| i as f64).collect()).unwrap();
+        ts.add_channel("Cz", (0..10).map(|i| -(i as f64)).collect()).unwrap();
+        ts.add_metadata("subject", "S001");
+        
+        let segments = ts.split_fixed(3);
+        assert_eq!(segments.len(), 3);
+        for segment in &segments {
+            assert_eq!(segment.channels.len(), 2);
+            assert!(segment.data.iter().all(|channel| channel.len() == 3));
+            assert_eq!(segment.timestamps.len(), 3);
+            assert_eq!(segment.timestamps[0], 0.0);
+            assert_eq!(segment.metadata.get("subject").unwrap(), "S001");
+        }
+        assert_eq!(segments[1].get_channel_data("Fz").unwrap(), &vec![3.0, 4.0, 5.0]);
+        assert_eq!(segments[2].get_channel_data("Cz").unwrap(), &vec![-6.0, -7.0, -8.0]);
+        
+        let padded = ts.split_fixed_padded(3, 0.0);
+        assert_eq!(padded.len(), 4);
+        assert_eq!(padded[3].get_channel_data("Fz").unwrap(), &vec![9.0, 0.0, 0.0]);
+        assert_eq!(padded[3].timestamps.len(), 3);
+    }
+    
+    #[test]
+    fn test_entropy() {
+        let fs = 256.0;
+        let uniform = seeded_noise(7, 65536);
+        let tone: Vec<f64> = (0..65536)
+            .map(|i| (2.0 * std::f64::consts::PI * 16.0 * i as f64 / fs).sin())
+            .collect();
+        
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, fs, "microvolts");
+        ts.add_channel("Flat", vec![3.5; 65536]).unwrap();
+        ts.add_channel("Noise", uniform).unwrap();
+        ts.add_channel("Tone", tone).unwrap();
+        
+        assert!(ts.shannon_entropy("Flat", 16).unwrap() < 1e-12);
+        let noise_entropy = ts.shannon_entropy("Noise", 16).unwrap();
+        assert!((noise_entropy - 4.0).abs() < 0.01, "entropy {}", noise_entropy);
+        assert!(ts.shannon_entropy("Noise", 0).is_err());
+        assert!(ts.shannon_entropy("Cz", 16).is_err());
+        
+        let tone_spectral = ts.spectral_entropy("Tone").unwrap();
+        let noise_spectral = ts.spectral_entropy("Noise").unwrap();
+        assert!(tone_spectral < 0.1, "tone spectral entropy {}", tone_spectral);
+        assert!(noise_spectral > 10.0, "noise spectral entropy {}", noise_spectral);
+    }
+    
+    #[test]
+    fn test_select_channels() {
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 256.0, "microvolts");
+        ts.generate_timestamps(0.0, 3);
+        for (i, name) in ["Fp1", "Fz", "Cz", "C3"].iter().enumerate() {
+            ts.add_channel(name, vec![i as f64; 3]).unwrap();
+        }
+        ts.set_position("Cz", (0.0, 0.0, 1.0)).unwrap();
+        ts.add_metadata("subject", "S001");
+        
+        let midline = ts.select_channels(&["Cz".to_string(), "Fz".to_string()]).unwrap();
+        assert_eq!(midline.channels, vec!["Cz", "Fz"]);
+        assert_eq!(midline.data, vec![vec![2.0; 3], vec![1.0; 3]]);
+        assert_eq!(midline.timestamps, ts.timestamps);
+        assert_eq!(midline.metadata.get("subject").unwrap(), "S001");
+        assert_eq!(midline.position("Cz"), Some((0.0, 0.0, 1.0)));
+        
+        assert!(ts.select_channels(&["Cz".to_string(), "Pz".to_string()]).is_err());
+        
+        let z_line = ts.select_channels_matching(|name| name.ends_with('z'));
+        assert_eq!(z_line.channels, vec!["Fz", "Cz"]);
+        assert_eq!(z_line.sampling_rate, 256.0);
+    }
+    
+    #[test]
+    fn test_moving_rms() {
+        let fs = 250.0;
+        let amplitude = 20.0;
+        let data: Vec<f64> = (0..1000)
+            .map(|i| amplitude * (2.0 * std::f64::consts::PI * 10.0 * i as f64 / fs).sin())
+            .collect();
+        
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, fs, "microvolts");
+        ts.add_channel("Cz", data).unwrap();
+        
+        // Two full periods of the 10 Hz sine
+        let envelope = ts.moving_rms("Cz", 50).unwrap();
+        assert_eq!(envelope.len(), 1000);
+        for &value in &envelope[25..975] {
+            assert!((value - amplitude / 2f64.sqrt()).abs() < 1e-6, "rms {}", value);
+        }
+        
+        let identity = ts.moving_rms("Cz", 1).unwrap();
+        assert!((identity[10] - ts.get_channel_data("Cz").unwrap()[10].abs()).abs() < 1e-9);
+        
+        assert!(ts.moving_rms("Cz", 0).is_err());
+        assert!(ts.moving_rms("Oz", 10).is_err());
+    }
+    
+    #[test]
+    fn test_common_spatial_patterns() {
+        let mixing = [[1.0, 0.4], [0.5, 1.0], [0.8, 0.8]];
+        
+        // Class A is dominated by the first source, class B by the second
+        let trial = |source_gains: [f64; 2], seed: u64| {
+            let first = seeded_noise(seed, 500);
+            let second = seeded_noise(seed + 100, 500);
+            let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 250.0, "microvolts");
+            for (c, (name, weights)) in ["C3", "Cz", "C4"].iter().zip(&mixing).enumerate() {
+                let sensor_noise = seeded_noise(seed + 200 + c as u64, 500);
+                let data = (0..500)
+                    .map(|i| {
+                        weights[0] * source_gains[0] * first[i]
+                            + weights[1] * source_gains[1] * second[i]
+                            + 0.05 * sensor_noise[i]
+                    })
+                    .collect();
+                ts.add_channel(name, data).unwrap();
+            }
+            ts
+        };
+        let class_a: Vec<NeuralTimeSeries> = (0..5).map(|k| trial([5.0, 1.0], k)).collect();
+        let class_b: Vec<NeuralTimeSeries> = (5..10).map(|k| trial([1.0, 5.0], k)).collect();
+        
+        let filters = common_spatial_patterns(&class_a, &class_b, 1).unwrap();
+        assert_eq!(filters.len(), 2);
+        assert!(filters.iter().all(|f| f.len() == 3));
+        
+        let filtered_variance = |filter: &[f64], trials: &[NeuralTimeSeries]| -> f64 {
+            trials
+                .iter()
+                .map(|ts| {
+                    let projected: Vec<f64> = (0..ts.data[0].len())
+                        .map(|t| filter.iter().zip(&ts.data).map(|(w, channel)| w * channel[t]).sum())
+                        .collect();
+                    projected.iter().map(|v| v * v).sum::<f64>() / projected.len() as f64
+                })
+                .sum::<f64>()
+                / trials.len() as f64
+        };
+        let ratio_first = filtered_variance(&filters[0], &class_a) / filtered_variance(&filters[0], &class_b);
+        let ratio_last = filtered_variance(&filters[1], &class_a) / filtered_variance(&filters[1], &class_b);
+        assert!(ratio_first > 10.0, "first filter ratio {}", ratio_first);
+        assert!(ratio_last < 0.1, "last filter ratio {}", ratio_last);
+        
+        // Mismatched channel sets and impossible component counts are rejected
+        let mut other = vec![trial([1.0, 5.0], 10)];
+        other[0].channels[2] = "Pz".to_string();
+        assert!(common_spatial_patterns(&class_a, &other, 1).is_err());
+        assert!(common_spatial_patterns(&class_a, &class_b, 2).is_err());
+        assert!(common_spatial_patterns(&class_a, &[], 1).is_err());
+    }
+    
+    #[test]
+    fn test_find_peaks() {
+        let mut data = vec![0.0f64; 200];
+        for (center, height) in [(30usize, 10.0), (100, 8.0), (160, 12.0), (106, 5.0)] {
+            for (i, value) in data.iter_mut().enumerate().skip(center - 3).take(7) {
+                *value = value.max(height - i.abs_diff(center) as f64);
+            }
+        }
+        
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 100.0, "microvolts");
+        ts.add_channel("Cz", data).unwrap();
+        
+        // The smaller peak at 106 is within 20 samples of the one at 100
+        assert_eq!(ts.find_peaks("Cz", 1.0, 20).unwrap(), vec![30, 100, 160]);
+        assert_eq!(ts.find_peaks("Cz", 1.0, 1).unwrap(), vec![30, 100, 106, 160]);
+        assert_eq!(ts.find_peaks("Cz", 9.0, 20).unwrap(), vec![30, 160]);
+        
+        assert!(ts.find_peaks("Oz", 1.0, 20).is_err());
+    }
+    
+    #[test]
+    fn test_clipping() {
+        let mut data: Vec<f64> = (0..100).map(|i| (i as f64 * 0.37).sin() * 80.0).collect();
+        for i in [10, 11, 12, 50, 51] {
+            data[i] = 100.0;
+        }
+        
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 256.0, "microvolts");
+        ts.add_channel("Fp1", data).unwrap();
+        ts.add_channel("Cz", (0..100).map(|i| (i as f64 * 0.37).sin() * 80.0).collect()).unwrap();
+        
+        assert_eq!(ts.detect_clipping("Fp1", -100.0, 100.0), vec![10, 11, 12, 50, 51]);
+        assert!((ts.clipping_fraction("Fp1", -100.0, 100.0) - 0.05).abs() < 1e-12);
+        
+        assert!(ts.detect_clipping("Cz", -100.0, 100.0).is_empty());
+        assert_eq!(ts.clipping_fraction("Cz", -100.0, 100.0), 0.0);
+        assert_eq!(ts.clipping_fraction("Oz", -100.0, 100.0), 0.0);
+        
+        // Integer ADC codes repeat their extremes without touching the rails
+        ts.add_channel("Pz", (0..100).map(|i| ((i as f64 * 0.37).sin() * 80.0).round()).collect()).unwrap();
+        assert_eq!(ts.clipping_fraction("Pz", -100.0, 100.0), 0.0);
+    }
+    
+    #[test]
+    fn test_trim_padding() {
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 100.0, "microvolts");
+        ts.generate_timestamps(0.0, 10);
+        ts.add_channel("Fz", vec![0.0, 0.0, 0.0, 5.0, -3.0, 0.0, 2.0, 0.0, 0.0, 0.0]).unwrap();
+        ts.add_channel("Cz", vec![0.0, 0.01, 0.0, 1.0, 0.0, 0.0, 0.0, 4.0, 0.0, 0.0]).unwrap();
+        
+        let (start, end) = ts.trim(0.1);
+        assert_eq!((start, end), (3, 8));
+        assert_eq!(ts.get_channel_data("Fz").unwrap(), &vec![5.0, -3.0, 0.0, 2.0, 0.0]);
+        assert_eq!(ts.get_channel_data("Cz").unwrap(), &vec![1.0, 0.0, 0.0, 0.0, 4.0]);
+        assert_eq!(ts.timestamps.len(), 5);
+        assert!((ts.timestamps[0] - 0.03).abs() < 1e-12);
+    }
+    
+    #[test]
+    fn test_band_power_ratio() {
+        let fs = 256.0;
+        let wave = |freq: f64, amplitude: f64, i: usize| amplitude * (2.0 * std::f64::consts::PI * freq * i as f64 / fs).sin();
+        // Strong 6 Hz theta with a weaker 20 Hz beta component
+        let data: Vec<f64> = (0..1024).map(|i| wave(6.0, 5.0, i) + wave(20.0, 1.0, i)).collect();
+        
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, fs, "microvolts");
+        ts.add_channel("Fz", data).unwrap();
+        ts.add_channel("flat", vec![3.0; 1024]).unwrap();
+        
+        // Power scales with amplitude squared
+        let ratio = ts.theta_beta_ratio("Fz").unwrap();
+        assert!((ratio - 25.0).abs() < 0.5, "theta/beta {}", ratio);
+        let inverse = ts.band_power_ratio("Fz", BETA_BAND, THETA_BAND).unwrap();
+        assert!((inverse * ratio - 1.0).abs() < 1e-9);
+        
+        assert!(ts.theta_beta_ratio("flat").is_err());
+        assert!(ts.band_power_ratio("Fz", THETA_BAND, (30.0, 20.0)).is_err());
+        assert!(ts.theta_beta_ratio("Cz").is_err());
+    }
+    
+    #[test]
+    fn test_snr() {
+        let fs = 256.0;
+        let data: Vec<f64> = seeded_noise(12345, 1024)
+            .into_iter()
+            .enumerate()
+            .map(|(i, noise)| 10.0 * (2.0 * std::f64::consts::PI * 10.0 * i as f64 / fs).sin() + noise)
+            .collect();
+        
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, fs, "microvolts");
+        ts.add_channel("Oz", data).unwrap();
+        
+        // A 10 uV sine carries 50 uV^2 of power
+        let signal_power = ts.band_power("Oz", (8.0, 12.0)).unwrap();
+        assert!((signal_power - 50.0).abs() < 2.0, "signal power {}", signal_power);
+        
+        let snr = ts.snr("Oz", (8.0, 12.0), (30.0, 60.0)).unwrap();
+        assert!(snr > 20.0, "snr {}", snr);
+        
+        assert!(ts.snr("Oz", (8.0, 12.0), (10.0, 20.0)).is_err());
+        assert!(ts.snr("Oz", (8.0, 12.0), (100.0, 200.0)).is_err());
+        assert!(ts.snr("Cz", (8.0, 12.0), (30.0, 60.0)).is_err());
+    }
+    
+    #[test]
+    fn test_dominant_frequency() {
+        let fs = 256.0;
+        let n = 1000;
+        let data: Vec<f64> = (0..n)
+            .map(|i| 3.0 + (2.0 * std::f64::consts::PI * 15.0 * i as f64 / fs).sin())
+            .collect();
+        
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, fs, "microvolts");
+        ts.add_channel("O1", data).unwrap();
+        ts.add_channel("Empty", vec![0.0]).unwrap();
+        
+        let bin_width = fs / n as f64;
+        let peak = ts.dominant_frequency("O1").unwrap();
+        assert!((peak - 15.0).abs() <= bin_width, "peak {}", peak);
+        
+        assert!(ts.dominant_frequency("Empty").is_err());
+        assert!(ts.dominant_frequency("Cz").is_err());
+    }
+    
+    #[test]
+    fn test_cross_correlation_lag() {
+        let source = seeded_noise(42, 520);
+        
+        // B receives A's signal 7 samples later
+        let delay = 7;
+        let a = source[delay..delay + 500].to_vec();
+        let b = source[..500].to_vec();
+        
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::ECOG, 1000.0, "microvolts");
+        ts.add_channel("A", a).unwrap();
+        ts.add_channel("B", b).unwrap();
+        ts.add_channel("Flat", vec![1.0; 500]).unwrap();
+        
+        let (lag, correlation) = ts.cross_correlation_lag("A", "B", 20).unwrap();
+        assert_eq!(lag, delay as i64);
+        assert!((correlation - 1.0).abs() < 1e-9, "correlation {}", correlation);
+        
+        let (lag, _) = ts.cross_correlation_lag("B", "A", 20).unwrap();
+        assert_eq!(lag, -(delay as i64));
+        
+        assert!(ts.cross_correlation_lag("A", "Flat", 20).is_err());
+        assert!(ts.cross_correlation_lag("A", "B", 499).is_err());
+        assert!(ts.cross_correlation_lag("A", "Cz", 20).is_err());
+    }
+    
+    #[test]
+    fn test_fft_filter_matches_direct_convolution() {
+        let fs = 256.0;
+        let data: Vec<f64> = (0..1000)
+            .map(|i| {
+                let t = i as f64 / fs;
+                (2.0 * std::f64::consts::PI * 5.0 * t).sin() + 0.5 * (2.0 * std::f64::consts::PI * 80.0 * t).sin()
+            })
+            .collect();
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, fs, "microvolts");
+        ts.add_channel("Fz", data.clone()).unwrap();
+        
+        let taps = ts.design_lowpass_fir(30.0, 31).unwrap();
+        assert_eq!(taps.len(), 31);
+        assert!((taps.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        
+        let filtered = ts.fft_filter("Fz", &taps).unwrap();
+        let direct: Vec<f64> = (0..data.len())
+            .map(|i| {
+                taps.iter()
+                    .enumerate()
+                    .filter(|(k, _)| *k <= i)
+                    .map(|(k, tap)| tap * data[i - k])
+                    .sum()
+            })
+            .collect();
+        
+        assert_eq!(filtered.len(), data.len());
+        for (a, b) in filtered.iter().zip(&direct) {
+            assert!((a - b).abs() < 1e-9);
+        }
+        
+        assert!(ts.fft_filter("Cz", &taps).is_err());
+        assert!(ts.design_lowpass_fir(200.0, 31).is_err());
+    }
+    
+    #[test]
+    fn test_compressed_json_round_trip() {
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 256.0, "microvolts");
+        ts.generate_timestamps(0.0, 2560);
+        
+        for ch in 0..32 {
+            let data: Vec<f64> = (0..2560)
+                .map(|i| {
+                    let t = i as f64 / 256.0;
+                    (2.0 * std::f64::consts::PI * 10.0 * t + ch as f64).sin() * 25.0
+                        + (2.0 * std::f64::consts::PI * 23.7 * t).sin() * 4.0
+                })
+                .collect();
+            ts.add_channel(&format!("Ch{}", ch + 1), data).unwrap();
+        }
+        ts.add_metadata("subject", "S001");
+        
+        let plain = ts.to_json().unwrap();
+        let compressed = ts.to_json_compressed().unwrap();
+        assert!(compressed.len() < plain.len());
+        
+        let restored = NeuralTimeSeries::from_json_compressed(&compressed).unwrap();
+        assert_eq!(restored.to_json().unwrap(), plain);
+    }
+    
+    #[test]
+    fn test_json_rounded() {
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 256.0, "microvolts");
+        ts.generate_timestamps(0.0, 1000);
+        let data: Vec<f64> = (0..1000).map(|i| (i as f64 * 0.1).sin() * 42.123456789).collect();
+        ts.add_channel("Cz", data.clone()).unwrap();
+        
+        let full = ts.to_json().unwrap();
+        let rounded = ts.to_json_rounded(2).unwrap();
+        assert!(rounded.len() < full.len());
+        
+        let restored = NeuralTimeSeries::from_json(&rounded).unwrap();
+        assert_eq!(restored.timestamps, ts.timestamps);
+        for (original, value) in data.iter().zip(restored.get_channel_data("Cz").unwrap()) {
+            assert!((original - value).abs() <= 0.005 + 1e-12);
+            assert!(value.to_string().split('.').nth(1).map_or(0, |d| d.len()) <= 2);
+        }
+        
+        let max_precision = NeuralTimeSeries::from_json(&ts.to_json_rounded(u8::MAX).unwrap()).unwrap();
+        assert_eq!(max_precision.get_channel_data("Cz").unwrap(), &data);
+    }
+    
+    #[test]
+    fn test_estimated_size() {
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 256.0, "microvolts");
+        assert_eq!(ts.estimated_size_bytes(), ts.to_json().unwrap().len());
+        
+        ts.generate_timestamps(0.0, 5000);
+        for (ch, name) in ["Fz", "Cz", "Pz", "Oz"].iter().enumerate() {
+            let data: Vec<f64> = (0..5000)
+                .map(|i| (2.0 * std::f64::consts::PI * 10.0 * i as f64 / 256.0 + ch as f64).sin() * 25.0)
+                .collect();
+            ts.add_channel(name, data).unwrap();
+        }
+        ts.add_metadata("subject", "S001");
+        
+        let actual = ts.to_json().unwrap().len() as f64;
+        let estimate = ts.estimated_size_bytes() as f64;
+        assert!((estimate - actual).abs() / actual < 0.05, "estimate {} vs actual {}", estimate, actual);
+        
+        let compressed = ts.to_json_compressed().unwrap().len() as f64;
+        let compressed_estimate = ts.estimated_compressed_size_bytes() as f64;
+        assert!((compressed_estimate - compressed).abs() / compressed < 0.25,
+                "estimate {} vs actual {}", compressed_estimate, compressed);
+    }
+    
+    #[test]
+    fn test_analysis_cache() {
+        use std::cell::Cell;
+        
+        let mut ts = NeuralTimeSeries::new(NeuralDataFormat::EEG, 256.0, "microvolts");
+        ts.add_channel("Oz", (0..512).map(|i| (i as f64 * 0.25).sin()).collect()).unwrap();
+        ts.add_metadata("subject", "S001");
+        ts.add_metadata("session", "1");
+        let hash = ts.content_hash().unwrap();
+        
+        let copy = NeuralTimeSeries::from_json(&ts.to_json().unwrap()).unwrap();
+        assert_eq!(copy.content_hash().unwrap(), hash);
+        
+        let computed = Cell::new(0);
+        let mut cache = AnalysisCache::new(2);
+        let alpha_power = |cache: &mut AnalysisCache, params: &str| -> f64 {
+            cache
+                .get_or_compute(&hash, "band_power", params, || {
+                    computed.set(computed.get() + 1);
+                    ts.band_power("Oz", (8.0, 12.0))
+                })
+                .unwrap()
+        };
+        
+        let first = alpha_power(&mut cache, "[8,12]");
+        let second = alpha_power(&mut cache, "[8,12]");
+        assert_eq!(first, second);
+        assert_eq!(computed.get(), 1);
+        
+        // Different parameters are cached separately; the oldest entry is evicted at capacity
+        alpha_power(&mut cache, "[8,13]");
+        alpha_power(&mut cache, "[8,14]");
+        assert_eq!(computed.get(), 3);
+        assert_eq!(cache.len(), 2);
+        alpha_power(&mut cache, "[8,12]");
+        assert_eq!(computed.get(), 4);
+        
+        let failed: Result<f64, _> = cache.get_or_compute(&hash, "snr", "", || Err("no noise band".into()));
+        assert!(failed.is_err());
+        assert_eq!(cache.len(), 2);
+        
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+    
     #[test]
     fn test_brain_study_metadata() {
         let mut metadata = BrainStudyMetadata::new("S001", "EEG Study", "University Hospital");
@@ -1,9 +1,10 @@
 use wasm_bindgen::prelude::*;
+use std::cell::RefCell;
 use std::error::Error;
 use serde::{Serialize, Deserialize};
 
-use crate::neural_data::{NeuralDataFormat, NeuralTimeSeries};
-use crate::blockchain::{Transaction, TransactionType};
+use crate::neural_data::{AnalysisCache, ChannelStatistics, NeuralDataFormat, NeuralTimeSeries};
+use crate::blockchain::{Blockchain, Transaction, TransactionType};
 use crate::crypto;
 use crate::ipfs;
 
@@ -57,6 +58,61 @@ impl WasmNeuroscienceDataset {
     }
 }
 
+/// WASM导出的区块链封装
+#[wasm_bindgen]
+pub struct WasmBlockchain {
+    inner: Blockchain,
+}
+
+#[wasm_bindgen]
+impl WasmBlockchain {
+    #[wasm_bindgen(constructor)]
+    pub fn new(difficulty: u8, mining_reward: u64) -> Self {
+        WasmBlockchain {
+            inner: Blockchain::new(difficulty, mining_reward),
+        }
+    }
+
+    /// 登记发送方地址对应的公钥
+    #[wasm_bindgen]
+    pub fn register_public_key(&mut self, address: &str, public_key: &str) {
+        self.inner.register_public_key(address, public_key);
+    }
+
+    /// 签名并提交一笔数据提交交易，返回交易ID
+    #[wasm_bindgen]
+    pub fn submit_transaction(&mut self, sender: &str, data: &str, private_key: &str) -> Result<String, JsValue> {
+        match self.inner.submit(TransactionType::DataSubmission, sender, data, private_key) {
+            Ok(id) => Ok(id),
+            Err(err) => Err(JsValue::from_str(&format!("提交错误: {}", err))),
+        }
+    }
+
+    /// 以JS数组形式返回待处理交易
+    #[wasm_bindgen]
+    pub fn pending_transactions_json(&self) -> Result<JsValue, JsValue> {
+        match JsValue::from_serde(&self.inner.pending_transactions) {
+            Ok(value) => Ok(value),
+            Err(err) => Err(JsValue::from_str(&format!("序列化错误: {}", err))),
+        }
+    }
+
+    /// 待处理交易数量
+    #[wasm_bindgen]
+    pub fn pending_count(&self) -> usize {
+        self.inner.pending_transactions.len()
+    }
+
+    /// 返回交易的最终性状态，形如 { status, confirmations? }，供钱包界面显示
+    #[wasm_bindgen]
+    pub fn finality_status(&self, tx_id: &str, required_confirmations: u64) -> Result<JsValue, JsValue> {
+        match JsValue::from_serde(&self.inner.finality_status(tx_id, required_confirmations)) {
+            Ok(value) => Ok(value),
+            Err(err) => Err(JsValue::from_str(&format!("序列化错误: {}", err))),
+        }
+    }
+}
+
 /// WASM导出的函数，用于哈希数据
 #[wasm_bindgen]
 pub fn hash_data(data: &str) -> String {
@@ -92,6 +148,39 @@ pub fn decrypt_data(encrypted_data: &str, key: &str) -> Result<String, JsValue>
     }
 }
 
+/// 数据集加密所用的算法描述
+const DATASET_ENCRYPTION_ALGORITHM: &str = "AES-256-GCM/Argon2id";
+
+/// WASM导出的函数，使用口令加密数据集JSON，返回 { ciphertext, salt, algorithm }
+#[wasm_bindgen]
+pub fn encrypt_dataset(json: &str, password: &str) -> Result<JsValue, JsValue> {
+    let salt = crypto::generate_salt();
+    let key = crypto::derive_key(password, &salt)
+        .map_err(|err| JsValue::from_str(&format!("密钥派生错误: {}", err)))?;
+    let ciphertext = crypto::encrypt_aes_gcm(json, &key)
+        .map_err(|err| JsValue::from_str(&format!("加密错误: {}", err)))?;
+    
+    let result = serde_json::json!({
+        "ciphertext": ciphertext,
+        "salt": salt,
+        "algorithm": DATASET_ENCRYPTION_ALGORITHM,
+    });
+    
+    match JsValue::from_serde(&result) {
+        Ok(js_val) => Ok(js_val),
+        Err(err) => Err(JsValue::from_str(&format!("序列化错误: {}", err))),
+    }
+}
+
+/// WASM导出的函数，使用口令解密 `encrypt_dataset` 生成的数据
+#[wasm_bindgen]
+pub fn decrypt_dataset(ciphertext: &str, salt: &str, password: &str) -> Result<String, JsValue> {
+    let key = crypto::derive_key(password, salt)
+        .map_err(|err| JsValue::from_str(&format!("密钥派生错误: {}", err)))?;
+    crypto::decrypt_aes_gcm(ciphertext, &key)
+        .map_err(|err| JsValue::from_str(&format!("解密错误: {}", err)))
+}
+
 /// WASM导出的函数，用于创建模拟的IPFS上传
 #[wasm_bindgen]
 pub fn upload_to_ipfs(content: &str, name: &str) -> Result<JsValue, JsValue> {
@@ -126,12 +215,9 @@ pub fn create_neural_data_transaction(
     private_key: &str
 ) -> Result<JsValue, JsValue> {
     // 创建一个数据访问交易
-    let mut tx = Transaction::new(
-        TransactionType::DataAccess,
-        sender,
-        &format!("Access granted to data: {}", data_id),
-    ).with_recipient(recipient)
-     .with_gas_fee(21000);
+    let mut tx = Transaction::data_access(sender, data_id)
+        .with_recipient(recipient)
+        .with_gas_fee(21000);
     
     // 签名交易
     match tx.sign(private_key) {
@@ -188,32 +274,102 @@ pub fn init() {
     log::info!("NeuraDeSci WASM模块已初始化!");
 }
 
+/// 分析结果缓存的最大条目数
+const ANALYSIS_CACHE_ENTRIES: usize = 32;
+
+thread_local! {
+    /// 按输入内容哈希缓存的分析结果，相同数据重复分析时直接返回
+    static ANALYSIS_CACHE: RefCell<AnalysisCache> = RefCell::new(AnalysisCache::new(ANALYSIS_CACHE_ENTRIES));
+}
+
 // 模拟数据分析函数
 #[wasm_bindgen]
 pub fn analyze_eeg_data(json_data: &str) -> Result<JsValue, JsValue> {
-    // 解析EEG数据
-    let eeg: NeuralTimeSeries = match serde_json::from_str(json_data) {
-        Ok(data) => data,
+    let content_hash = crypto::hash_sha256(json_data);
+    
+    // 计算每个通道的统计数据，相同输入命中缓存时跳过解析和计算
+    let results: Vec<ChannelStatistics> = ANALYSIS_CACHE
+        .with(|cache| {
+            cache.borrow_mut().get_or_compute(&content_hash, "channel_stats", "", || {
+                let eeg: NeuralTimeSeries = serde_json::from_str(json_data)
+                    .map_err(|err| format!("解析错误: {}", err))?;
+                Ok(eeg
+                    .channels
+                    .iter()
+                    .filter_map(|channel| eeg.calculate_channel_stats(channel))
+                    .collect())
+            })
+        })
+        .map_err(|err| JsValue::from_str(&err.to_string()))?;
+    
+    // 转换为JS对象并返回
+    match JsValue::from_serde(&results) {
+        Ok(value) => Ok(value),
+        Err(err) => Err(JsValue::from_str(&format!("序列化错误: {}", err))),
+    }
+}
+
+/// 逐通道分析数据，每处理完一个通道就以 0 到 1 之间的进度调用 `callback`
+///
+/// 最后一次回调的进度为 1.0；没有通道时也会回调一次 1.0。返回值与 `analyze_eeg_data` 相同。
+#[wasm_bindgen]
+pub fn analyze_with_progress(json_data: &str, callback: &js_sys::Function) -> Result<JsValue, JsValue> {
+    let eeg = match NeuralTimeSeries::from_json(json_data) {
+        Ok(eeg) => eeg,
         Err(err) => return Err(JsValue::from_str(&format!("解析错误: {}", err))),
     };
     
-    // 分析结果
-    let mut results = Vec::new();
-    
-    // 计算每个通道的统计数据
-    for channel in &eeg.channels {
+    let total = eeg.channels.len();
+    let mut results: Vec<ChannelStatistics> = Vec::with_capacity(total);
+    for (i, channel) in eeg.channels.iter().enumerate() {
         if let Some(stats) = eeg.calculate_channel_stats(channel) {
             results.push(stats);
         }
+        callback.call1(&JsValue::NULL, &JsValue::from_f64((i + 1) as f64 / total as f64))?;
+    }
+    if total == 0 {
+        callback.call1(&JsValue::NULL, &JsValue::from_f64(1.0))?;
     }
     
-    // 转换为JS对象并返回
     match JsValue::from_serde(&results) {
         Ok(value) => Ok(value),
         Err(err) => Err(JsValue::from_str(&format!("序列化错误: {}", err))),
     }
 }
 
+/// 提取单个通道的数据并转换为f32数组，供WebGL可视化直接使用（映射为Float32Array）
+#[wasm_bindgen]
+pub fn channel_to_float32(json_data: &str, channel: &str) -> Result<Box<[f32]>, JsValue> {
+    let series = match NeuralTimeSeries::from_json(json_data) {
+        Ok(series) => series,
+        Err(err) => return Err(JsValue::from_str(&format!("解析错误: {}", err))),
+    };
+    
+    match series.get_channel_data(channel) {
+        Some(data) => Ok(data.iter().map(|&value| value as f32).collect()),
+        None => Err(JsValue::from_str(&format!("未找到通道: {}", channel))),
+    }
+}
+
+/// 估算数据上传前的存储大小，返回 { json_bytes, compressed_bytes }，供界面提示大文件上传
+#[wasm_bindgen]
+pub fn estimate_upload_size(json_data: &str) -> Result<JsValue, JsValue> {
+    let series = match NeuralTimeSeries::from_json(json_data) {
+        Ok(series) => series,
+        Err(err) => return Err(JsValue::from_str(&format!("解析错误: {}", err))),
+    };
+    
+    let result = serde_json::json!({
+        "json_bytes": series.estimated_size_bytes(),
+        "compressed_bytes": series.estimated_compressed_size_bytes(),
+    });
+    
+    match JsValue::from_serde(&result) {
+        Ok(js_val) => Ok(js_val),
+        Err(err) => Err(JsValue::from_str(&format!("序列化错误: {}", err))),
+    }
+}
+
 /// JavaScript示例代码生成函数
 #[wasm_bindgen]
 pub fn get_js_usage_example() -> String {
@@ -296,4 +452,81 @@ pub fn run_tests() -> JsValue {
     };
     
     JsValue::from_serde(&result).unwrap_or(JsValue::NULL)
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_channel_to_float32() {
+        let mut eeg = NeuralTimeSeries::new(NeuralDataFormat::EEG, 256.0, "microvolts");
+        eeg.generate_timestamps(0.0, 4);
+        eeg.add_channel("Fz", vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+        let json = eeg.to_json().unwrap();
+        
+        let samples = channel_to_float32(&json, "Fz").unwrap();
+        assert_eq!(samples.len(), eeg.get_channel_data("Fz").unwrap().len());
+        assert_eq!(samples[2], 3.0f32);
+        
+        assert!(channel_to_float32(&json, "Cz").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_analyze_with_progress() {
+        let mut eeg = NeuralTimeSeries::new(NeuralDataFormat::EEG, 256.0, "microvolts");
+        eeg.generate_timestamps(0.0, 4);
+        for channel in ["Fz", "Cz", "Pz"] {
+            eeg.add_channel(channel, vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+        }
+        let json = eeg.to_json().unwrap();
+        
+        let progress = std::rc::Rc::new(RefCell::new(Vec::new()));
+        let recorder = progress.clone();
+        let callback = Closure::wrap(Box::new(move |fraction: f64| {
+            recorder.borrow_mut().push(fraction);
+        }) as Box<dyn FnMut(f64)>);
+        
+        let results = analyze_with_progress(&json, callback.as_ref().unchecked_ref()).unwrap();
+        assert_eq!(js_sys::Array::from(&results).length(), 3);
+        
+        // 每个通道回调一次，进度单调递增并以 1.0 结束
+        let progress = progress.borrow();
+        assert_eq!(progress.len(), 3);
+        assert!(progress.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(progress.iter().all(|&fraction| fraction > 0.0 && fraction <= 1.0));
+        assert_eq!(progress.last(), Some(&1.0));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_pending_transactions_json() {
+        let mut chain = WasmBlockchain::new(1, 50);
+        let (private_key, public_key) = crypto::generate_keypair();
+        chain.register_public_key("researcher_001", &public_key);
+        
+        let tx_id = chain.submit_transaction("researcher_001", "dataset upload", &private_key).unwrap();
+        assert_eq!(chain.pending_count(), 1);
+        
+        let pending = js_sys::Array::from(&chain.pending_transactions_json().unwrap());
+        assert_eq!(pending.length(), 1);
+        let id = js_sys::Reflect::get(&pending.get(0), &JsValue::from_str("id")).unwrap();
+        assert_eq!(id.as_string(), Some(tx_id));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_dataset_encryption_round_trip() {
+        let json = r#"{"id":"ds-001","title":"Resting-state EEG","keywords":["EEG"]}"#;
+        let encrypted = encrypt_dataset(json, "correct horse battery staple").unwrap();
+        let field = |name: &str| {
+            js_sys::Reflect::get(&encrypted, &JsValue::from_str(name))
+                .unwrap()
+                .as_string()
+                .unwrap()
+        };
+        
+        let decrypted = decrypt_dataset(&field("ciphertext"), &field("salt"), "correct horse battery staple").unwrap();
+        assert_eq!(decrypted, json);
+        assert!(decrypt_dataset(&field("ciphertext"), &field("salt"), "wrong password").is_err());
+    }
+}